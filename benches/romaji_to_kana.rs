@@ -0,0 +1,125 @@
+//! Compares the trie-based romaji matcher with the linear scan it replaced.
+//!
+//! Run with `cargo bench --bench romaji_to_kana`.
+
+use std::hint::black_box;
+
+use buchikun::core::romaji_to_kana::romaji_to_kana;
+use buchikun::core::table::{ROMAJI_TO_KANA, Table};
+use criterion::{Criterion, criterion_group, criterion_main};
+
+const SAMPLES: &[&str] = &[
+    "konnichiha",
+    "toukyouekinochikakuniarukissatende",
+    "kyouhaiitenkidesune",
+    "gakkounoshukudaiwoyaranakereba",
+    "konpyu-ta-puroguramingu",
+    "shinkansenninotteoosakahemukau",
+    "watashinonamaehayamadataroudesu",
+    "dhisukuwofo-matto",
+    "matchanokissatenhakinnjonianna",
+];
+
+/// `Table::match_romaji` before the trie: the first entry, longest first, that `s` starts with.
+fn match_romaji_linear(s: &str) -> Option<(usize, &'static str)> {
+    ROMAJI_TO_KANA
+        .iter()
+        .find(|(romaji, _)| s.starts_with(romaji))
+        .map(|(romaji, kana)| (romaji.len(), *kana))
+}
+
+/// `romaji_to_kana` before the trie, in Hiragana and without strict mode.
+fn romaji_to_kana_linear(input: &str) -> String {
+    let mut result = String::new();
+    let mut current_idx = 0;
+    while current_idx < input.len() {
+        let remaining = &input[current_idx..];
+
+        // "nn" is ん. Before a vowel or 'y' only the first 'n' is consumed.
+        if let Some(after) = remaining.strip_prefix("nn") {
+            let next_starts_syllable = after.starts_with(['a', 'i', 'u', 'e', 'o', 'y']);
+            result.push('ん');
+            current_idx += if next_starts_syllable { 1 } else { 2 };
+            continue;
+        }
+
+        if let Some((len, kana)) = match_romaji_linear(remaining) {
+            result.push_str(kana);
+            current_idx += len;
+            continue;
+        }
+
+        // A doubled consonant or "tch" is っ
+        let first_char = remaining.chars().next().unwrap();
+        let first_char_len = first_char.len_utf8();
+        if remaining.len() > first_char_len {
+            let next_char = remaining[first_char_len..].chars().next().unwrap();
+            if (first_char == next_char && is_consonant(first_char))
+                || (first_char == 't' && remaining[first_char_len..].starts_with("ch"))
+            {
+                result.push('っ');
+                current_idx += first_char_len;
+                continue;
+            }
+        }
+
+        result.push(first_char);
+        current_idx += first_char_len;
+    }
+    result
+}
+
+fn is_consonant(c: char) -> bool {
+    match c {
+        'a' | 'i' | 'u' | 'e' | 'o' | 'n' => false,
+        _ => c.is_ascii_alphabetic(),
+    }
+}
+
+fn bench_match_romaji(c: &mut Criterion) {
+    let corpus = SAMPLES.concat();
+    let table = Table::new();
+    for start in 0..corpus.len() {
+        assert_eq!(
+            table.match_romaji(&corpus[start..]),
+            match_romaji_linear(&corpus[start..]),
+            "at {}",
+            start
+        );
+    }
+
+    let mut group = c.benchmark_group("match_romaji");
+    group.bench_function("linear scan", |b| {
+        b.iter(|| {
+            for start in 0..corpus.len() {
+                black_box(match_romaji_linear(black_box(&corpus[start..])));
+            }
+        })
+    });
+    group.bench_function("trie", |b| {
+        b.iter(|| {
+            for start in 0..corpus.len() {
+                black_box(table.match_romaji(black_box(&corpus[start..])));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_romaji_to_kana(c: &mut Criterion) {
+    let corpus = SAMPLES.concat();
+    assert_eq!(romaji_to_kana(&corpus), romaji_to_kana_linear(&corpus));
+    for sample in SAMPLES {
+        assert_eq!(romaji_to_kana(sample), romaji_to_kana_linear(sample));
+    }
+
+    let mut group = c.benchmark_group("romaji_to_kana");
+    group.bench_function("linear scan", |b| {
+        b.iter(|| romaji_to_kana_linear(black_box(&corpus)))
+    });
+    group.bench_function("trie", |b| b.iter(|| romaji_to_kana(black_box(&corpus))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_match_romaji, bench_romaji_to_kana);
+criterion_main!(benches);
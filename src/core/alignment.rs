@@ -0,0 +1,140 @@
+use std::ops::Range;
+
+/// A piece of input and the output it was converted to.
+///
+/// 入力の範囲と、それから変換された出力の範囲（バイト単位）です。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// Byte range in the input.
+    pub source: Range<usize>,
+    /// Byte range in the output.
+    pub output: Range<usize>,
+}
+
+/// Which part of the input produced which part of the output.
+///
+/// 変換前と変換後の文字列の対応関係です。
+///
+/// The spans are in order, do not overlap, and together cover both the whole input and
+/// the whole output. A span may have an empty output, e.g. for input that is dropped.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::romaji_to_kana::romaji_to_kana_aligned;
+///
+/// let (kana, alignment) = romaji_to_kana_aligned("toukyou");
+/// assert_eq!(kana, "とうきょう");
+///
+/// // Highlight the romaji for a match on "きょう" in the output.
+/// let start = kana.find("きょう").unwrap();
+/// let source = alignment.source_range(start..start + "きょう".len());
+/// assert_eq!(&"toukyou"[source], "kyou");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Alignment {
+    spans: Vec<Span>,
+}
+
+impl Alignment {
+    pub(crate) fn new(spans: Vec<Span>) -> Self {
+        Alignment { spans }
+    }
+
+    /// The spans, in input order.
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// The smallest input range that produced every output byte in `output`.
+    ///
+    /// An empty `output` maps to an empty range at the matching input position.
+    pub fn source_range(&self, output: Range<usize>) -> Range<usize> {
+        map_range(
+            &self.spans,
+            output,
+            |span| &span.output,
+            |span| &span.source,
+        )
+    }
+
+    /// The smallest output range produced by every input byte in `source`.
+    pub fn output_range(&self, source: Range<usize>) -> Range<usize> {
+        map_range(
+            &self.spans,
+            source,
+            |span| &span.source,
+            |span| &span.output,
+        )
+    }
+}
+
+fn map_range(
+    spans: &[Span],
+    range: Range<usize>,
+    from: impl Fn(&Span) -> &Range<usize>,
+    to: impl Fn(&Span) -> &Range<usize>,
+) -> Range<usize> {
+    let mut touched = spans
+        .iter()
+        .filter(|span| from(span).start < range.end && range.start < from(span).end);
+    match touched.next() {
+        Some(first) => {
+            let last = touched.last().unwrap_or(first);
+            to(first).start..to(last).end
+        }
+        None => {
+            // Nothing overlaps: map to the position right after the preceding spans.
+            let position = spans
+                .iter()
+                .take_while(|span| from(span).end <= range.start)
+                .last()
+                .map_or(0, |span| to(span).end);
+            position..position
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Alignment {
+        // "kyat" -> "きゃ" + "t"
+        Alignment::new(vec![
+            Span {
+                source: 0..3,
+                output: 0..6,
+            },
+            Span {
+                source: 3..4,
+                output: 6..7,
+            },
+        ])
+    }
+
+    #[test]
+    fn test_source_range() {
+        let alignment = sample();
+        assert_eq!(alignment.source_range(0..6), 0..3);
+        assert_eq!(alignment.source_range(3..6), 0..3);
+        assert_eq!(alignment.source_range(3..7), 0..4);
+        assert_eq!(alignment.source_range(6..6), 3..3);
+        assert_eq!(alignment.source_range(7..7), 4..4);
+    }
+
+    #[test]
+    fn test_output_range() {
+        let alignment = sample();
+        assert_eq!(alignment.output_range(1..2), 0..6);
+        assert_eq!(alignment.output_range(3..4), 6..7);
+        assert_eq!(alignment.output_range(0..0), 0..0);
+    }
+
+    #[test]
+    fn test_empty() {
+        let alignment = Alignment::default();
+        assert!(alignment.spans().is_empty());
+        assert_eq!(alignment.source_range(0..0), 0..0);
+    }
+}
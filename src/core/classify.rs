@@ -0,0 +1,355 @@
+/// Whether `c` is Hiragana, including the small letters and the iteration marks ゝ and ゞ.
+///
+/// ひらがなかどうかを判定します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::classify::is_hiragana;
+/// assert!(is_hiragana('あ'));
+/// assert!(is_hiragana('ゞ'));
+/// assert!(!is_hiragana('ア'));
+/// assert!(!is_hiragana('ー'));
+/// ```
+pub fn is_hiragana(c: char) -> bool {
+    matches!(c, 'ぁ'..='ゖ' | 'ゝ'..='ゟ')
+}
+
+/// Whether `c` is Katakana, including the prolonged sound mark ー, the iteration marks,
+/// the Ainu small letters (ㇰ, ...) and half-width Katakana.
+///
+/// カタカナかどうかを判定します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::classify::is_katakana;
+/// assert!(is_katakana('ア'));
+/// assert!(is_katakana('ー'));
+/// assert!(is_katakana('ｱ'));
+/// assert!(!is_katakana('あ'));
+/// ```
+pub fn is_katakana(c: char) -> bool {
+    matches!(c, 'ァ'..='ヺ' | 'ー'..='ヿ' | 'ㇰ'..='ㇿ' | 'ｦ'..='ﾝ')
+}
+
+/// Whether `c` is Hiragana or Katakana.
+///
+/// かな（ひらがな・カタカナ）かどうかを判定します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::classify::is_kana;
+/// assert!(is_kana('あ'));
+/// assert!(is_kana('ア'));
+/// assert!(!is_kana('亜'));
+/// ```
+pub fn is_kana(c: char) -> bool {
+    is_hiragana(c) || is_katakana(c)
+}
+
+/// Whether `c` is a Kanji: a CJK ideograph from the unified blocks (including the extensions)
+/// or the compatibility blocks, or one of 々, 〆 and 〇.
+///
+/// 漢字かどうかを判定します。拡張漢字・互換漢字と「々」「〆」「〇」も含みます。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::classify::is_kanji;
+/// assert!(is_kanji('漢'));
+/// assert!(is_kanji('々'));
+/// assert!(is_kanji('𠮟'));
+/// assert!(!is_kanji('か'));
+/// ```
+pub fn is_kanji(c: char) -> bool {
+    matches!(
+        c,
+        '々' | '〆' | '〇'
+            // Extension A
+            | '\u{3400}'..='\u{4DBF}'
+            // Unified Ideographs
+            | '\u{4E00}'..='\u{9FFF}'
+            // Compatibility Ideographs
+            | '\u{F900}'..='\u{FAFF}'
+            // Extensions B to H and the Compatibility Ideographs Supplement
+            | '\u{20000}'..='\u{323AF}'
+    )
+}
+
+/// Whether `c` is a small kana such as っ, ゃ or ァ.
+///
+/// 小書きのかな（捨て仮名）かどうかを判定します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::classify::is_kana_small;
+/// assert!(is_kana_small('っ'));
+/// assert!(is_kana_small('ャ'));
+/// assert!(is_kana_small('ｧ'));
+/// assert!(!is_kana_small('つ'));
+/// ```
+pub fn is_kana_small(c: char) -> bool {
+    matches!(
+        c,
+        'ぁ' | 'ぃ'
+            | 'ぅ'
+            | 'ぇ'
+            | 'ぉ'
+            | 'っ'
+            | 'ゃ'
+            | 'ゅ'
+            | 'ょ'
+            | 'ゎ'
+            | 'ゕ'
+            | 'ゖ'
+            | 'ァ'
+            | 'ィ'
+            | 'ゥ'
+            | 'ェ'
+            | 'ォ'
+            | 'ッ'
+            | 'ャ'
+            | 'ュ'
+            | 'ョ'
+            | 'ヮ'
+            | 'ヵ'
+            | 'ヶ'
+            | 'ㇰ'..='ㇿ'
+            | 'ｧ'..='ｯ'
+    )
+}
+
+/// Whether `c` is Japanese punctuation: the CJK punctuation marks (、。「」『』【】〜 ...),
+/// the middle dot ・, and their full-width and half-width forms (！？（） ｡｢｣ ...).
+///
+/// 日本語の句読点・括弧などの記号かどうかを判定します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::classify::is_japanese_punctuation;
+/// assert!(is_japanese_punctuation('。'));
+/// assert!(is_japanese_punctuation('「'));
+/// assert!(is_japanese_punctuation('！'));
+/// assert!(!is_japanese_punctuation('!'));
+/// assert!(!is_japanese_punctuation('ー'));
+/// ```
+pub fn is_japanese_punctuation(c: char) -> bool {
+    matches!(
+        c,
+        '、'..='〃'
+            | '〈'..='】'
+            | '〔'..='〟'
+            | '〰'
+            | '〽'
+            | '゠'
+            | '・'
+            // Full-width ASCII punctuation
+            | '！'..='／'
+            | '：'..='＠'
+            | '［'..='｀'
+            | '｛'..='｠'
+            // Half-width 。「」、・
+            | '｡'..='･'
+    )
+}
+
+/// The script a character is written in, as used by [`split_by_script`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Script {
+    Hiragana,
+    Katakana,
+    Kanji,
+    /// Latin letters, including full-width (Ａ) and accented (ō) ones.
+    Latin,
+    /// Anything else: digits, spaces, punctuation, other scripts.
+    Other,
+}
+
+/// The script `c` is written in.
+///
+/// 文字の種類（ひらがな・カタカナ・漢字・ラテン文字・その他）を返します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::classify::{script_of, Script};
+/// assert_eq!(script_of('あ'), Script::Hiragana);
+/// assert_eq!(script_of('漢'), Script::Kanji);
+/// assert_eq!(script_of('ō'), Script::Latin);
+/// assert_eq!(script_of('1'), Script::Other);
+/// ```
+pub fn script_of(c: char) -> Script {
+    if is_hiragana(c) {
+        Script::Hiragana
+    } else if is_katakana(c) {
+        Script::Katakana
+    } else if is_kanji(c) {
+        Script::Kanji
+    } else if c.is_alphabetic() && matches!(c, 'A'..='z' | 'À'..='ɏ' | 'Ḁ'..='ỿ' | 'Ａ'..='ｚ')
+    {
+        Script::Latin
+    } else {
+        Script::Other
+    }
+}
+
+/// Split `input` into runs of characters written in the same script.
+///
+/// 文字列を文字の種類ごとに分割します。
+///
+/// The prolonged sound mark ー and the (semi-)voiced sound marks, combining (U+3099, U+309A)
+/// or spacing (゛, ゜), continue the run before them, so "すーぱー" and "か\u{3099}な"
+/// each stay one Hiragana run.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::classify::{split_by_script, Script};
+///
+/// let runs: Vec<_> = split_by_script("東京タワーへGO").collect();
+/// assert_eq!(
+///     runs,
+///     [
+///         (Script::Kanji, "東京"),
+///         (Script::Katakana, "タワー"),
+///         (Script::Hiragana, "へ"),
+///         (Script::Latin, "GO"),
+///     ]
+/// );
+/// ```
+pub fn split_by_script(input: &str) -> ScriptRuns<'_> {
+    ScriptRuns { rest: input }
+}
+
+/// Iterator over the runs of [`split_by_script`].
+#[derive(Debug, Clone)]
+pub struct ScriptRuns<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for ScriptRuns<'a> {
+    type Item = (Script, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chars = self.rest.char_indices();
+        let (_, first) = chars.next()?;
+        let script = script_of(first);
+        let end = chars
+            .find(|&(_, c)| script_of(c) != script && !continues_run(c, script))
+            .map_or(self.rest.len(), |(i, _)| i);
+        let (run, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some((script, run))
+    }
+}
+
+/// Whether `c` belongs to the run before it even though its script differs.
+fn continues_run(c: char, script: Script) -> bool {
+    match c {
+        'ー' => script == Script::Hiragana,
+        // Combining and spacing (semi-)voiced sound marks
+        '\u{3099}'..='\u{309C}' => matches!(script, Script::Hiragana | Script::Katakana),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kana() {
+        for c in "ぁあゔゖゝゞゟ".chars() {
+            assert!(is_hiragana(c) && !is_katakana(c), "{}", c);
+        }
+        for c in "ァアヴヶヷヺーヽヾヿㇰｦｱﾝｰ".chars() {
+            assert!(is_katakana(c) && !is_hiragana(c), "{}", c);
+        }
+        for c in "・゛゜\u{3099}亜a".chars() {
+            assert!(!is_kana(c), "{}", c);
+        }
+    }
+
+    #[test]
+    fn test_kanji() {
+        for c in "一鿿㐀々〆〇豈𠀀𪛖𰀀丽".chars() {
+            assert!(is_kanji(c), "{}", c);
+        }
+        for c in "〃ゝヽあア、".chars() {
+            assert!(!is_kanji(c), "{}", c);
+        }
+    }
+
+    #[test]
+    fn test_kana_small() {
+        for c in "ぁぃぅぇぉっゃゅょゎゕゖァィゥェォッャュョヮヵヶㇰㇿｧｯ".chars()
+        {
+            assert!(is_kana_small(c), "{}", c);
+        }
+        for c in "あつやアツヤｱﾂ".chars() {
+            assert!(!is_kana_small(c), "{}", c);
+        }
+    }
+
+    #[test]
+    fn test_japanese_punctuation() {
+        for c in "、。〃「」『』【】〔〕〜・！？（）：；［］｛｝｡｢｣､･".chars()
+        {
+            assert!(is_japanese_punctuation(c), "{}", c);
+        }
+        for c in "々〆ー!?.あＡ１".chars() {
+            assert!(!is_japanese_punctuation(c), "{}", c);
+        }
+    }
+
+    #[test]
+    fn test_script_of() {
+        assert_eq!(script_of('ｶ'), Script::Katakana);
+        assert_eq!(script_of('Ｚ'), Script::Latin);
+        assert_eq!(script_of('é'), Script::Latin);
+        assert_eq!(script_of('α'), Script::Other);
+        assert_eq!(script_of('。'), Script::Other);
+        assert_eq!(script_of(' '), Script::Other);
+    }
+
+    #[test]
+    fn test_split_by_script() {
+        let runs: Vec<_> = split_by_script("すーぱーでパンを3個かう").collect();
+        assert_eq!(
+            runs,
+            [
+                (Script::Hiragana, "すーぱーで"),
+                (Script::Katakana, "パン"),
+                (Script::Hiragana, "を"),
+                (Script::Other, "3"),
+                (Script::Kanji, "個"),
+                (Script::Hiragana, "かう"),
+            ]
+        );
+
+        let runs: Vec<_> = split_by_script("か\u{3099}ーー").collect();
+        assert_eq!(runs, [(Script::Hiragana, "か\u{3099}ーー")]);
+
+        let runs: Vec<_> = split_by_script("ハ゜ン゛").collect();
+        assert_eq!(runs, [(Script::Katakana, "ハ゜ン゛")]);
+
+        let runs: Vec<_> = split_by_script("\u{3099}漢\u{3099}").collect();
+        assert_eq!(
+            runs,
+            [
+                (Script::Other, "\u{3099}"),
+                (Script::Kanji, "漢"),
+                (Script::Other, "\u{3099}")
+            ]
+        );
+
+        let runs: Vec<_> = split_by_script("ーあ").collect();
+        assert_eq!(runs, [(Script::Katakana, "ー"), (Script::Hiragana, "あ")]);
+
+        assert_eq!(split_by_script("").next(), None);
+    }
+}
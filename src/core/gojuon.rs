@@ -0,0 +1,404 @@
+use super::kana::{to_hiragana, to_katakana};
+
+/// A consonant row (行) of the gojūon table, including the voiced rows.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Row {
+    A,  // あ行
+    Ka, // か行
+    Sa, // さ行
+    Ta, // た行
+    Na, // な行
+    Ha, // は行
+    Ma, // ま行
+    Ya, // や行
+    Ra, // ら行
+    Wa, // わ行
+    Ga, // が行
+    Za, // ざ行
+    Da, // だ行
+    Ba, // ば行
+    Pa, // ぱ行
+}
+
+/// A vowel column (段) of the gojūon table.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Column {
+    A, // あ段
+    I, // い段
+    U, // う段
+    E, // え段
+    O, // お段
+}
+
+const ROWS: [Row; 15] = [
+    Row::A,
+    Row::Ka,
+    Row::Sa,
+    Row::Ta,
+    Row::Na,
+    Row::Ha,
+    Row::Ma,
+    Row::Ya,
+    Row::Ra,
+    Row::Wa,
+    Row::Ga,
+    Row::Za,
+    Row::Da,
+    Row::Ba,
+    Row::Pa,
+];
+
+const COLUMNS: [Column; 5] = [Column::A, Column::I, Column::U, Column::E, Column::O];
+
+/// Marks a cell without a kana of its own, e.g. yi in the や row.
+const GAP: char = '\0';
+
+/// The gojūon table in Hiragana, in the order of [`ROWS`] and [`COLUMNS`].
+///
+/// い, う and え appear only in the あ row; the わ row has the historical ゐ and ゑ.
+const GRID: [[char; 5]; 15] = [
+    ['あ', 'い', 'う', 'え', 'お'],
+    ['か', 'き', 'く', 'け', 'こ'],
+    ['さ', 'し', 'す', 'せ', 'そ'],
+    ['た', 'ち', 'つ', 'て', 'と'],
+    ['な', 'に', 'ぬ', 'ね', 'の'],
+    ['は', 'ひ', 'ふ', 'へ', 'ほ'],
+    ['ま', 'み', 'む', 'め', 'も'],
+    ['や', GAP, 'ゆ', GAP, 'よ'],
+    ['ら', 'り', 'る', 'れ', 'ろ'],
+    ['わ', 'ゐ', GAP, 'ゑ', 'を'],
+    ['が', 'ぎ', 'ぐ', 'げ', 'ご'],
+    ['ざ', 'じ', 'ず', 'ぜ', 'ぞ'],
+    ['だ', 'ぢ', 'づ', 'で', 'ど'],
+    ['ば', 'び', 'ぶ', 'べ', 'ぼ'],
+    ['ぱ', 'ぴ', 'ぷ', 'ぺ', 'ぽ'],
+];
+
+/// Small kana and their full-size counterparts, in Hiragana.
+const SMALL: [(char, char); 12] = [
+    ('ぁ', 'あ'),
+    ('ぃ', 'い'),
+    ('ぅ', 'う'),
+    ('ぇ', 'え'),
+    ('ぉ', 'お'),
+    ('っ', 'つ'),
+    ('ゃ', 'や'),
+    ('ゅ', 'ゆ'),
+    ('ょ', 'よ'),
+    ('ゎ', 'わ'),
+    ('ゕ', 'か'),
+    ('ゖ', 'け'),
+];
+
+/// The row and column of a kana in the gojūon table.
+///
+/// 五十音表での行と段を返します。
+///
+/// Works for Hiragana and Katakana. Small kana are placed where their full-size
+/// counterparts are. ん and other characters have no place in the table.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::gojuon::{position, Column, Row};
+/// assert_eq!(position('く'), Some((Row::Ka, Column::U)));
+/// assert_eq!(position('ボ'), Some((Row::Ba, Column::O)));
+/// assert_eq!(position('っ'), Some((Row::Ta, Column::U)));
+/// assert_eq!(position('ん'), None);
+/// ```
+pub fn position(c: char) -> Option<(Row, Column)> {
+    let large = to_large(c).unwrap_or(c);
+    let hiragana = to_hiragana(large);
+    GRID.iter().enumerate().find_map(|(row, kana)| {
+        let column = kana.iter().position(|&k| k == hiragana && k != GAP)?;
+        Some((ROWS[row], COLUMNS[column]))
+    })
+}
+
+/// The Hiragana at `row` and `column`, if the table has one there.
+///
+/// 五十音表の行と段にあたるひらがなを返します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::gojuon::{kana_at, Column, Row};
+/// assert_eq!(kana_at(Row::Sa, Column::I), Some('し'));
+/// assert_eq!(kana_at(Row::Ya, Column::E), None);
+/// ```
+pub fn kana_at(row: Row, column: Column) -> Option<char> {
+    let row = ROWS.iter().position(|&r| r == row)?;
+    let column = COLUMNS.iter().position(|&c| c == column)?;
+    Some(GRID[row][column]).filter(|&k| k != GAP)
+}
+
+/// Move a kana to another column of its row (く -> か, き, け, こ).
+///
+/// かなを同じ行の別の段に移します。
+///
+/// The script and size are kept: Katakana stay Katakana, and small kana stay small
+/// (None if the result has no small form).
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::gojuon::{shift_column, Column};
+/// assert_eq!(shift_column('く', Column::A), Some('か'));
+/// assert_eq!(shift_column('ム', Column::I), Some('ミ'));
+/// assert_eq!(shift_column('ゃ', Column::O), Some('ょ'));
+/// assert_eq!(shift_column('ゆ', Column::I), None);
+/// ```
+pub fn shift_column(c: char, column: Column) -> Option<char> {
+    let (row, _) = position(c)?;
+    let shifted = kana_at(row, column)?;
+    let shifted = if is_small(c) {
+        to_small(shifted)?
+    } else {
+        shifted
+    };
+    Some(same_script(c, shifted))
+}
+
+/// The voiced (dakuten) form: か -> が, は -> ば, ぱ -> ば, う -> ゔ.
+///
+/// 濁音にします。
+///
+/// In Katakana, ワ, ヰ, ヱ and ヲ also become ヷ, ヸ, ヹ and ヺ. Small kana (っ, ゕ, ...)
+/// have no voiced form.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::gojuon::voice;
+/// assert_eq!(voice('か'), Some('が'));
+/// assert_eq!(voice('ホ'), Some('ボ'));
+/// assert_eq!(voice('ワ'), Some('ヷ'));
+/// assert_eq!(voice('な'), None);
+/// assert_eq!(voice('っ'), None);
+/// ```
+pub fn voice(c: char) -> Option<char> {
+    if is_small(c) {
+        return None;
+    }
+    match c {
+        'う' => return Some('ゔ'),
+        'ウ' => return Some('ヴ'),
+        'ワ' => return Some('ヷ'),
+        'ヰ' => return Some('ヸ'),
+        'ヱ' => return Some('ヹ'),
+        'ヲ' => return Some('ヺ'),
+        _ => {}
+    }
+    let voiced = match position(c)? {
+        (Row::Ka, column) => (Row::Ga, column),
+        (Row::Sa, column) => (Row::Za, column),
+        (Row::Ta, column) => (Row::Da, column),
+        (Row::Ha | Row::Pa, column) => (Row::Ba, column),
+        _ => return None,
+    };
+    move_to(c, voiced)
+}
+
+/// The semi-voiced (handakuten) form: は -> ぱ, ば -> ぱ.
+///
+/// 半濁音にします。
+///
+/// Small kana have no semi-voiced form.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::gojuon::semi_voice;
+/// assert_eq!(semi_voice('ひ'), Some('ぴ'));
+/// assert_eq!(semi_voice('ブ'), Some('プ'));
+/// assert_eq!(semi_voice('か'), None);
+/// ```
+pub fn semi_voice(c: char) -> Option<char> {
+    if is_small(c) {
+        return None;
+    }
+    match position(c)? {
+        (Row::Ha | Row::Ba, column) => move_to(c, (Row::Pa, column)),
+        _ => None,
+    }
+}
+
+/// The voiceless form: が -> か, ば -> は, ぱ -> は, ゔ -> う.
+///
+/// 清音にします。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::gojuon::devoice;
+/// assert_eq!(devoice('が'), Some('か'));
+/// assert_eq!(devoice('ポ'), Some('ホ'));
+/// assert_eq!(devoice('ヺ'), Some('ヲ'));
+/// assert_eq!(devoice('か'), None);
+/// ```
+pub fn devoice(c: char) -> Option<char> {
+    match c {
+        'ゔ' => return Some('う'),
+        'ヴ' => return Some('ウ'),
+        'ヷ' => return Some('ワ'),
+        'ヸ' => return Some('ヰ'),
+        'ヹ' => return Some('ヱ'),
+        'ヺ' => return Some('ヲ'),
+        _ => {}
+    }
+    let voiceless = match position(c)? {
+        (Row::Ga, column) => (Row::Ka, column),
+        (Row::Za, column) => (Row::Sa, column),
+        (Row::Da, column) => (Row::Ta, column),
+        (Row::Ba | Row::Pa, column) => (Row::Ha, column),
+        _ => return None,
+    };
+    move_to(c, voiceless)
+}
+
+/// The small form: つ -> っ, や -> ゃ, あ -> ぁ. Small kana are returned as is.
+///
+/// 小書きのかなにします。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::gojuon::to_small;
+/// assert_eq!(to_small('つ'), Some('っ'));
+/// assert_eq!(to_small('ヨ'), Some('ョ'));
+/// assert_eq!(to_small('っ'), Some('っ'));
+/// assert_eq!(to_small('き'), None);
+/// ```
+pub fn to_small(c: char) -> Option<char> {
+    let hiragana = to_hiragana(c);
+    SMALL
+        .iter()
+        .find(|&&(small, large)| large == hiragana || small == hiragana)
+        .map(|&(small, _)| same_script(c, small))
+}
+
+/// The full-size form: っ -> つ, ゃ -> や, ぁ -> あ. Full-size kana with a small form
+/// are returned as is.
+///
+/// 小書きのかなを普通の大きさにします。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::gojuon::to_large;
+/// assert_eq!(to_large('っ'), Some('つ'));
+/// assert_eq!(to_large('ャ'), Some('ヤ'));
+/// assert_eq!(to_large('つ'), Some('つ'));
+/// assert_eq!(to_large('き'), None);
+/// ```
+pub fn to_large(c: char) -> Option<char> {
+    let hiragana = to_hiragana(c);
+    SMALL
+        .iter()
+        .find(|&&(small, large)| small == hiragana || large == hiragana)
+        .map(|&(_, large)| same_script(c, large))
+}
+
+fn is_small(c: char) -> bool {
+    let hiragana = to_hiragana(c);
+    SMALL.iter().any(|&(small, _)| small == hiragana)
+}
+
+/// The kana at `(row, column)`, in the script of `c`.
+fn move_to(c: char, (row, column): (Row, Column)) -> Option<char> {
+    kana_at(row, column).map(|k| same_script(c, k))
+}
+
+/// `hiragana` in the script of `c`.
+fn same_script(c: char, hiragana: char) -> char {
+    if to_hiragana(c) != c {
+        to_katakana(hiragana)
+    } else {
+        hiragana
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        assert_eq!(position('あ'), Some((Row::A, Column::A)));
+        assert_eq!(position('い'), Some((Row::A, Column::I)));
+        assert_eq!(position('を'), Some((Row::Wa, Column::O)));
+        assert_eq!(position('ヂ'), Some((Row::Da, Column::I)));
+        assert_eq!(position('ぷ'), Some((Row::Pa, Column::U)));
+        assert_eq!(position('ゃ'), Some((Row::Ya, Column::A)));
+        assert_eq!(position('ヵ'), Some((Row::Ka, Column::A)));
+        for c in ['ん', 'ー', 'ゔ', 'a', '漢', GAP] {
+            assert_eq!(position(c), None, "{}", c);
+        }
+    }
+
+    #[test]
+    fn test_grid_round_trip() {
+        for row in ROWS {
+            for column in COLUMNS {
+                if let Some(kana) = kana_at(row, column) {
+                    assert_eq!(position(kana), Some((row, column)));
+                    assert_eq!(position(to_katakana(kana)), Some((row, column)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_shift_column() {
+        let u_to_a: Vec<_> = "うくぐすつぬふぶむる"
+            .chars()
+            .map(|c| shift_column(c, Column::A).unwrap())
+            .collect();
+        assert_eq!(u_to_a.iter().collect::<String>(), "あかがさたなはばまら");
+        assert_eq!(shift_column('く', Column::E), Some('け'));
+        assert_eq!(shift_column('ク', Column::O), Some('コ'));
+        assert_eq!(shift_column('っ', Column::A), None);
+        assert_eq!(shift_column('ぁ', Column::I), Some('ぃ'));
+        assert_eq!(shift_column('を', Column::U), None);
+        assert_eq!(shift_column('ん', Column::A), None);
+    }
+
+    #[test]
+    fn test_voicing() {
+        for (voiceless, voiced) in
+            ["かが", "しじ", "つづ", "へべ", "ウヴ", "ヲヺ"]
+                .iter()
+                .map(|pair| {
+                    let mut chars = pair.chars();
+                    (chars.next().unwrap(), chars.next().unwrap())
+                })
+        {
+            assert_eq!(voice(voiceless), Some(voiced));
+            assert_eq!(devoice(voiced), Some(voiceless));
+        }
+        assert_eq!(voice('ぱ'), Some('ば'));
+        assert_eq!(voice('が'), None);
+        assert_eq!(voice('わ'), None);
+        assert_eq!(semi_voice('ハ'), Some('パ'));
+        assert_eq!(semi_voice('ぱ'), None);
+        assert_eq!(devoice('ぴ'), Some('ひ'));
+        assert_eq!(devoice('ん'), None);
+        // Small kana keep their size, so they have no (semi-)voiced form
+        for c in ['っ', 'ッ', 'ゕ', 'ゖ', 'ヵ', 'ヶ', 'ぅ', 'ゥ', 'ゃ'] {
+            assert_eq!(voice(c), None, "{}", c);
+            assert_eq!(semi_voice(c), None, "{}", c);
+            assert_eq!(devoice(c), None, "{}", c);
+        }
+    }
+
+    #[test]
+    fn test_size() {
+        for (small, large) in SMALL {
+            assert_eq!(to_small(large), Some(small));
+            assert_eq!(to_large(small), Some(large));
+            assert_eq!(to_small(to_katakana(large)), Some(to_katakana(small)));
+        }
+        assert_eq!(to_small('ク'), None);
+        assert_eq!(to_large('ん'), None);
+    }
+}
@@ -0,0 +1,244 @@
+use super::gojuon::{devoice, semi_voice, voice};
+
+/// Combining voiced sound mark (dakuten), as in "わ\u{3099}".
+const COMBINING_DAKUTEN: char = '\u{3099}';
+/// Combining semi-voiced sound mark (handakuten), as in "は\u{309A}".
+const COMBINING_HANDAKUTEN: char = '\u{309A}';
+
+/// Convert Hiragana to Katakana. Other characters are returned as is.
+///
+/// ひらがなをカタカナに変換します。
+///
+/// Covers the small letters (ぁ, っ, ゕ, ...), ゔ and the iteration marks ゝ and ゞ.
+/// Kana followed by a (semi-)voiced sound mark are composed as in [`compose_sound_marks`];
+/// this turns わ, ゐ, ゑ and を with a combining dakuten (U+3099) into ヷ, ヸ, ヹ and ヺ,
+/// which is what [`katakana_to_hiragana`] writes for them.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana::hiragana_to_katakana;
+/// assert_eq!(hiragana_to_katakana("ひらがな"), "ヒラガナ");
+/// assert_eq!(hiragana_to_katakana("ゔぁいおりん"), "ヴァイオリン");
+/// assert_eq!(hiragana_to_katakana("いすゞ"), "イスヾ");
+/// assert_eq!(hiragana_to_katakana("漢字とABC"), "漢字トABC");
+/// ```
+pub fn hiragana_to_katakana(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    for c in input.chars() {
+        push_composed(&mut result, to_katakana(c));
+    }
+    result
+}
+
+/// Convert Katakana to Hiragana. Other characters are returned as is.
+///
+/// カタカナをひらがなに変換します。
+///
+/// Covers the small letters (ァ, ッ, ヵ, ...), ヴ and the iteration marks ヽ and ヾ.
+/// ヷ, ヸ, ヹ and ヺ have no Hiragana of their own, so they are written as わ, ゐ, ゑ and を
+/// followed by a combining dakuten (U+3099), their canonical decomposition.
+/// The prolonged sound mark ー and the middle dot ・ are shared by both scripts.
+/// Kana followed by a (semi-)voiced sound mark are composed first, as in
+/// [`compose_sound_marks`].
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana::{hiragana_to_katakana, katakana_to_hiragana};
+/// assert_eq!(katakana_to_hiragana("カタカナ"), "かたかな");
+/// assert_eq!(katakana_to_hiragana("ヴァイオリン"), "ゔぁいおりん");
+/// assert_eq!(katakana_to_hiragana("ヶ月"), "ゖ月");
+///
+/// let hiragana = katakana_to_hiragana("ヷイン");
+/// assert_eq!(hiragana, "わ\u{3099}いん");
+/// assert_eq!(hiragana_to_katakana(&hiragana), "ヷイン");
+/// ```
+pub fn katakana_to_hiragana(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    for c in compose_sound_marks(input).chars() {
+        let base = match c {
+            'ヷ' => 'わ',
+            'ヸ' => 'ゐ',
+            'ヹ' => 'ゑ',
+            'ヺ' => 'を',
+            _ => {
+                result.push(to_hiragana(c));
+                continue;
+            }
+        };
+        result.push(base);
+        result.push(COMBINING_DAKUTEN);
+    }
+    result
+}
+
+/// Compose kana followed by a voiced or semi-voiced sound mark into one character.
+///
+/// 濁点・半濁点（結合文字 U+3099・U+309A と「゛」「゜」）を前のかなと合成します。
+///
+/// Decomposed kana ("か" + U+3099) come from macOS file names and some PDFs. Only
+/// voiceless kana take a mark (か゛ -> が, ハ゜ -> パ); other marks are kept as is.
+/// Hiragana わ, ゐ, ゑ and を have no voiced form and keep their mark.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana::compose_sound_marks;
+/// assert_eq!(compose_sound_marks("か\u{3099}き\u{3099}"), "がぎ");
+/// assert_eq!(compose_sound_marks("ハ\u{309A}ン"), "パン");
+/// assert_eq!(compose_sound_marks("か゛"), "が");
+/// assert_eq!(compose_sound_marks("ア゛"), "ア゛");
+/// ```
+pub fn compose_sound_marks(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    for c in input.chars() {
+        push_composed(&mut result, c);
+    }
+    result
+}
+
+/// The kana `base` with the sound mark `mark`, if they compose.
+pub(crate) fn compose_mark(base: char, mark: char) -> Option<char> {
+    if devoice(base).is_some() {
+        // Already voiced or semi-voiced
+        return None;
+    }
+    match mark {
+        COMBINING_DAKUTEN | '゛' => voice(base),
+        COMBINING_HANDAKUTEN | '゜' => semi_voice(base),
+        _ => None,
+    }
+}
+
+/// Push `c` onto `result`, composing it with the last char if `c` is a sound mark for it.
+///
+/// Returns whether `c` was composed.
+pub(crate) fn push_composed(result: &mut String, c: char) -> bool {
+    if let Some(last) = result.chars().next_back()
+        && let Some(composed) = compose_mark(last, c)
+    {
+        result.pop();
+        result.push(composed);
+        true
+    } else {
+        result.push(c);
+        false
+    }
+}
+
+/// Convert one Hiragana character to Katakana. Other characters are returned as is.
+///
+/// ひらがな一文字をカタカナに変換します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana::to_katakana;
+/// assert_eq!(to_katakana('ぢ'), 'ヂ');
+/// assert_eq!(to_katakana('ゞ'), 'ヾ');
+/// assert_eq!(to_katakana('ー'), 'ー');
+/// ```
+pub fn to_katakana(c: char) -> char {
+    match c {
+        // ぁ (U+3041) ..= ゖ (U+3096) and ゝ, ゞ sit exactly 0x60 below ァ ..= ヶ and ヽ, ヾ.
+        'ぁ'..='ゖ' | 'ゝ' | 'ゞ' => char::from_u32(c as u32 + 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Convert one Katakana character to Hiragana. Other characters are returned as is.
+///
+/// カタカナ一文字をひらがなに変換します。
+///
+/// ヷ, ヸ, ヹ and ヺ are returned as is, as they have no single Hiragana;
+/// [`katakana_to_hiragana`] decomposes them.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana::to_hiragana;
+/// assert_eq!(to_hiragana('ヂ'), 'ぢ');
+/// assert_eq!(to_hiragana('ヽ'), 'ゝ');
+/// assert_eq!(to_hiragana('ヷ'), 'ヷ');
+/// ```
+pub fn to_hiragana(c: char) -> char {
+    match c {
+        'ァ'..='ヶ' | 'ヽ' | 'ヾ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hiragana_to_katakana() {
+        assert_eq!(hiragana_to_katakana("きゃっとふーど"), "キャットフード");
+        assert_eq!(
+            hiragana_to_katakana("ぁぃぅぇぉゃゅょゎ"),
+            "ァィゥェォャュョヮ"
+        );
+        assert_eq!(hiragana_to_katakana("ゕゖ"), "ヵヶ");
+        assert_eq!(hiragana_to_katakana("ゝゞ"), "ヽヾ");
+        assert_eq!(hiragana_to_katakana("カタカナ"), "カタカナ");
+        assert_eq!(hiragana_to_katakana(""), "");
+    }
+
+    #[test]
+    fn test_katakana_to_hiragana() {
+        assert_eq!(katakana_to_hiragana("キャットフード"), "きゃっとふーど");
+        assert_eq!(
+            katakana_to_hiragana("ァィゥェォャュョヮ"),
+            "ぁぃぅぇぉゃゅょゎ"
+        );
+        assert_eq!(katakana_to_hiragana("ヽヾ・"), "ゝゞ・");
+        assert_eq!(katakana_to_hiragana("ひらがな"), "ひらがな");
+    }
+
+    #[test]
+    fn test_voiced_w() {
+        assert_eq!(
+            katakana_to_hiragana("ヷヸヹヺ"),
+            "わ\u{3099}ゐ\u{3099}ゑ\u{3099}を\u{3099}"
+        );
+        assert_eq!(
+            hiragana_to_katakana(&katakana_to_hiragana("ヷヸヹヺ")),
+            "ヷヸヹヺ"
+        );
+        assert_eq!(hiragana_to_katakana("わ"), "ワ");
+    }
+
+    #[test]
+    fn test_compose_sound_marks() {
+        assert_eq!(
+            compose_sound_marks("は\u{3099}は\u{309A}ハ゛ハ゜ウ\u{3099}"),
+            "ばぱバパヴ"
+        );
+        assert_eq!(compose_sound_marks("か\u{3099}\u{3099}"), "が\u{3099}");
+        assert_eq!(compose_sound_marks("ぱ゛な゜"), "ぱ゛な゜");
+        assert_eq!(compose_sound_marks("\u{3099}か"), "\u{3099}か");
+        assert_eq!(compose_sound_marks("わ\u{3099}"), "わ\u{3099}");
+        assert_eq!(compose_sound_marks("っ\u{3099}ゕ゛"), "っ\u{3099}ゕ゛");
+        assert_eq!(compose_sound_marks(""), "");
+
+        assert_eq!(hiragana_to_katakana("か\u{3099}は゜"), "ガパ");
+        assert_eq!(katakana_to_hiragana("カ\u{3099}ワ゛"), "がわ\u{3099}");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        // Every Hiragana letter has a Katakana counterpart
+        for c in ('ぁ'..='ゖ').chain(['ゝ', 'ゞ']) {
+            assert_eq!(to_hiragana(to_katakana(c)), c);
+        }
+        for c in ('ァ'..='ヶ').chain(['ヽ', 'ヾ']) {
+            assert_eq!(to_katakana(to_hiragana(c)), c);
+        }
+        for c in ['ヷ', 'ヸ', 'ヹ', 'ヺ', 'ー', '・', 'ゟ', 'ヿ'] {
+            assert_eq!(to_hiragana(c), c);
+            assert_eq!(to_katakana(c), c);
+        }
+    }
+}
@@ -1,6 +1,13 @@
-/// Convert Katakana to Romaji (Hepburn system).
+use std::{fmt, io};
+
+use super::alignment::{Alignment, Span};
+use super::kana::{compose_mark, to_katakana};
+use super::segment::word_ranges;
+use super::table::{Table, default_table};
+
+/// Convert Kana (Katakana or Hiragana) to Romaji (Hepburn system).
 ///
-/// ヘボン式でカタカナをローマ字に変換します。
+/// ヘボン式でカタカナ・ひらがなをローマ字に変換します。
 /// https://ja.wikipedia.org/wiki/%E3%83%98%E3%83%9C%E3%83%B3%E5%BC%8F%E3%83%AD%E3%83%BC%E3%83%9E%E5%AD%97
 ///
 /// # Examples
@@ -8,14 +15,58 @@
 /// ```
 /// use buchikun::core::kana_to_romaji::kana_to_romaji_hepburn;
 /// assert_eq!(kana_to_romaji_hepburn("カタカナ"), "katakana");
+/// assert_eq!(kana_to_romaji_hepburn("ひらがな"), "hiragana");
+/// assert_eq!(kana_to_romaji_hepburn("キンエン"), "kin'en");
 /// ```
 pub fn kana_to_romaji_hepburn(input: &str) -> String {
-    convert_kana_to_romaji(input, System::Hepburn)
+    convert_kana_to_romaji(input, Rules::new(System::Hepburn))
+}
+
+/// Convert Kana (Katakana or Hiragana) to Romaji (Hepburn system) with options.
+///
+/// オプションを指定してヘボン式でローマ字に変換します。
+/// 長音の表記 (ō / ô / oh など) を選べます。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::{kana_to_romaji_hepburn_with, HepburnOptions, LongVowel};
+///
+/// let macron = HepburnOptions { long_vowel: LongVowel::Macron, ..Default::default() };
+/// assert_eq!(kana_to_romaji_hepburn_with("トーキョー", &macron), "tōkyō");
+/// assert_eq!(kana_to_romaji_hepburn_with("とうきょう", &macron), "tōkyō");
+///
+/// let passport = HepburnOptions { long_vowel: LongVowel::Oh, ..HepburnOptions::passport() };
+/// assert_eq!(kana_to_romaji_hepburn_with("おおの", &passport), "ohno");
+/// ```
+pub fn kana_to_romaji_hepburn_with(input: &str, options: &HepburnOptions) -> String {
+    convert_kana_to_romaji(input, Rules::hepburn_with(options))
 }
 
-/// Convert Katakana to Romaji (Kunrei system).
+/// Convert Kana (Katakana or Hiragana) to Romaji as printed in Japanese passports.
+///
+/// 外務省のパスポート表記 (ヘボン式) でローマ字に変換します。
+/// 長音は表記せず、ヲは "o"、B・M・P の前のンは "m"、チの前のッは "t" と書きます。
+///
+/// Long o can be written "oh" on request; use [`kana_to_romaji_hepburn_with`] with
+/// `LongVowel::Oh` and [`HepburnOptions::passport`] for that.
+/// Passports are printed in upper case, so apply `to_uppercase()` for the printed form.
 ///
-/// 訓令式でカタカナをローマ字に変換します。
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::kana_to_romaji_passport;
+/// assert_eq!(kana_to_romaji_passport("さいとう"), "saito");
+/// assert_eq!(kana_to_romaji_passport("ほんま"), "homma");
+/// assert_eq!(kana_to_romaji_passport("はっちょう"), "hatcho");
+/// ```
+pub fn kana_to_romaji_passport(input: &str) -> String {
+    kana_to_romaji_hepburn_with(input, &HepburnOptions::passport())
+}
+
+/// Convert Kana (Katakana or Hiragana) to Romaji (Kunrei system).
+///
+/// 訓令式でカタカナ・ひらがなをローマ字に変換します。
 /// https://ja.wikipedia.org/wiki/%E3%83%AD%E3%83%BC%E3%83%9E%E5%AD%97#%E8%A8%93%E4%BB%A4%E5%BC%8F%E3%81%AE%E8%A1%A8
 ///
 /// # Examples
@@ -23,86 +74,842 @@ pub fn kana_to_romaji_hepburn(input: &str) -> String {
 /// ```
 /// use buchikun::core::kana_to_romaji::kana_to_romaji_kunrei;
 /// assert_eq!(kana_to_romaji_kunrei("カタカナ"), "katakana");
+/// assert_eq!(kana_to_romaji_kunrei("ひらがな"), "hiragana");
 /// ```
 pub fn kana_to_romaji_kunrei(input: &str) -> String {
-    convert_kana_to_romaji(input, System::Kunrei)
+    convert_kana_to_romaji(input, Rules::new(System::Kunrei))
 }
 
-#[derive(Clone, Copy)]
-enum System {
-    Hepburn,
-    Kunrei,
+/// Convert Kana (Katakana or Hiragana) to Romaji (Nihon-shiki system, ISO 3602 Strict).
+///
+/// 日本式でカタカナ・ひらがなをローマ字に変換します。
+/// 訓令式と異なり、ヂ・ヅ・ヲなどを区別して表記します。
+/// https://ja.wikipedia.org/wiki/%E6%97%A5%E6%9C%AC%E5%BC%8F%E3%83%AD%E3%83%BC%E3%83%9E%E5%AD%97
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::kana_to_romaji_nihon_shiki;
+/// assert_eq!(kana_to_romaji_nihon_shiki("ハナヂ"), "hanadi");
+/// assert_eq!(kana_to_romaji_nihon_shiki("ツヅク"), "tuduku");
+/// assert_eq!(kana_to_romaji_nihon_shiki("キンエン"), "kin'en");
+/// ```
+///
+/// # Round trip
+///
+/// Nihon-shiki is lossless: [`romaji_to_kana`](crate::core::romaji_to_kana::romaji_to_kana)
+/// (or `romaji_to_katakana` for Katakana input) gives back the original kana,
+/// for any kana string made of
+///
+/// - the 46 basic syllables (except ゐ, ゑ) and their voiced / semi-voiced forms,
+/// - contracted sounds written with ゃ, ゅ, ょ after an い-row kana (きゃ, ぢょ, ...),
+/// - ん, and ー,
+/// - っ before a syllable starting with a consonant other than n.
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::kana_to_romaji_nihon_shiki;
+/// use buchikun::core::romaji_to_kana::romaji_to_kana;
+///
+/// for kana in ["がっこう", "ぢゃんけん", "きんえん", "こんにゃく", "はなぢ"] {
+///     assert_eq!(romaji_to_kana(&kana_to_romaji_nihon_shiki(kana)), kana);
+/// }
+/// ```
+pub fn kana_to_romaji_nihon_shiki(input: &str) -> String {
+    convert_kana_to_romaji(input, Rules::new(System::NihonShiki))
 }
 
-fn convert_kana_to_romaji(input: &str, system: System) -> String {
-    let chars: Vec<char> = input.chars().collect();
-    let mut result = String::new();
-    let mut i = 0;
+/// Convert Kana (Katakana or Hiragana) to Romaji with the system chosen at runtime.
+///
+/// 指定したローマ字の方式でカタカナ・ひらがなをローマ字に変換します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::{kana_to_romaji, System};
+/// assert_eq!(kana_to_romaji("チヂミ", System::Hepburn), "chijimi");
+/// assert_eq!(kana_to_romaji("チヂミ", System::Kunrei), "tizimi");
+/// assert_eq!(kana_to_romaji("チヂミ", System::NihonShiki), "tidimi");
+/// ```
+pub fn kana_to_romaji(input: &str, system: System) -> String {
+    convert_kana_to_romaji(input, Rules::new(system))
+}
+
+/// Convert Kana (Katakana or Hiragana) to Romaji using a custom [`Table`].
+///
+/// 独自の変換表を使ってカタカナ・ひらがなをローマ字に変換します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::{kana_to_romaji_with_table, System};
+/// use buchikun::core::table::Table;
+///
+/// let mut table = Table::new();
+/// table.insert_kana("ㇰ", "k", "k", "k"); // Ainu small ku
+/// assert_eq!(kana_to_romaji_with_table("トㇰ", System::Hepburn, &table), "tok");
+/// ```
+pub fn kana_to_romaji_with_table(input: &str, system: System, table: &Table) -> String {
+    convert_kana_to_romaji(
+        input,
+        Rules {
+            table,
+            ..Rules::new(system)
+        },
+    )
+}
+
+/// Convert Kana to Romaji, also returning which kana produced which romaji.
+///
+/// カタカナ・ひらがなをローマ字に変換し、入力と出力の対応関係も返します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::{kana_to_romaji_aligned, System};
+///
+/// let input = "トウキョウ";
+/// let (romaji, alignment) = kana_to_romaji_aligned(input, System::Hepburn);
+/// assert_eq!(romaji, "toukyou");
+/// let start = romaji.find("kyo").unwrap();
+/// assert_eq!(&input[alignment.source_range(start..start + 3)], "キョ");
+/// ```
+pub fn kana_to_romaji_aligned(input: &str, system: System) -> (String, Alignment) {
+    convert_kana_to_romaji_aligned(input, Rules::new(system))
+}
+
+/// Convert Kana to Romaji, rejecting characters that have no romaji.
+///
+/// カタカナ・ひらがなをローマ字に変換します。変換できない文字がある場合はエラーを返します。
+///
+/// [`kana_to_romaji`] drops such characters; here the first one is reported as
+/// [`KanaError::UnknownChar`]. Whitespace is never reported, as in
+/// [`try_romaji_to_kana`](crate::core::romaji_to_kana::try_romaji_to_kana).
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::{try_kana_to_romaji, KanaError, System};
+/// assert_eq!(try_kana_to_romaji("すし", System::Hepburn), Ok("sushi".to_string()));
+/// assert_eq!(
+///     try_kana_to_romaji("お寿司", System::Hepburn),
+///     Err(KanaError::UnknownChar { character: '寿', position: 3 })
+/// );
+/// ```
+pub fn try_kana_to_romaji(input: &str, system: System) -> Result<String, KanaError> {
+    let (result, warnings) = kana_to_romaji_with_warnings(input, system);
+    match warnings.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(result),
+    }
+}
+
+/// Convert Kana to Romaji like [`kana_to_romaji`], also reporting what was dropped.
+///
+/// カタカナ・ひらがなをローマ字に変換し、変換できずに落とした文字を警告として返します。
+///
+/// The warnings are the errors [`try_kana_to_romaji`] would report, in input order.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::{kana_to_romaji_with_warnings, KanaError, System};
+///
+/// let (romaji, warnings) = kana_to_romaji_with_warnings("ABCカナ", System::Hepburn);
+/// assert_eq!(romaji, "kana");
+/// assert_eq!(warnings.len(), 3);
+/// assert_eq!(warnings[2], KanaError::UnknownChar { character: 'C', position: 2 });
+/// ```
+pub fn kana_to_romaji_with_warnings(input: &str, system: System) -> (String, Vec<KanaError>) {
+    let rules = Rules::new(system);
+    let mut chars = Vec::new();
+    let mut offsets = Vec::new();
+    fold_kana(input, &mut chars, &mut offsets);
+    let mut result = String::with_capacity(input.len());
+    let mut warnings = Vec::new();
+    write_kana_to_romaji(
+        input,
+        &chars,
+        &offsets,
+        rules,
+        &mut result,
+        None,
+        Some(&mut warnings),
+    );
+    (result, warnings)
+}
+
+/// Error returned by [`try_kana_to_romaji`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum KanaError {
+    /// A character with no romaji, at the given byte offset.
+    UnknownChar { character: char, position: usize },
+}
+
+/// Romanization system used by [`kana_to_romaji`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum System {
+    Hepburn,    // ヘボン式
+    Kunrei,     // 訓令式
+    NihonShiki, // 日本式
+}
 
+/// Options for [`kana_to_romaji_hepburn_with`].
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct HepburnOptions {
+    pub long_vowel: LongVowel,
+    /// Restrict spellings to the Ministry of Foreign Affairs' passport table.
+    ///
+    /// ヲ is written "o", the ヴ row is written with a b (ヴ -> "bu", ヴァ -> "ba"), and
+    /// only the standard contracted sounds (キャ, シュ, チョ, ...) are combined. Other small
+    /// kana are spelled one by one, e.g. ティ -> "tei".
+    pub passport_spellings: bool,
+    /// Letter case of the output. Passports print names in [`Case::Upper`].
+    pub case: Case,
+    /// What to do with characters that have no romaji.
+    pub pass_through: PassThrough,
+    /// How ン is separated from a following vowel or y. Passports do not separate it.
+    pub n_separator: NSeparator,
+    /// Write the particles は, へ and を as pronounced: "wa", "e" and "o".
+    ///
+    /// Particles are found by [`segment_words`](crate::core::segment::segment_words), which
+    /// uses rules rather than a dictionary: "わたしはがくせいです" -> "watashiwagakuseidesu",
+    /// but particles it misses keep "ha" and "he". Input that is already spaced, e.g. by a
+    /// morphological analyzer, is read as is. Combine it with [`PassThrough::Keep`] to keep
+    /// the spaces in the output: "わたし は がくせい です" -> "watashi wa gakusei desu".
+    pub phonetic_particles: bool,
+}
+
+impl HepburnOptions {
+    /// Options used by [`kana_to_romaji_passport`].
+    pub fn passport() -> Self {
+        HepburnOptions {
+            long_vowel: LongVowel::Omit,
+            passport_spellings: true,
+            case: Case::Lower,
+            pass_through: PassThrough::Drop,
+            n_separator: NSeparator::Omit,
+            phonetic_particles: false,
+        }
+    }
+}
+
+/// How long vowels are written.
+///
+/// Applies to the prolonged sound mark (ー) and to the vowel sequences おう, おお and うう.
+/// Other sequences such as えい or いい are always written as is.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum LongVowel {
+    /// Write vowels as spelled and ー as "-" (トーキョー -> "to-kyo-", トウキョウ -> "toukyou").
+    #[default]
+    AsWritten,
+    /// Modified Hepburn: "tōkyō".
+    Macron,
+    /// Circumflex, as typed where macrons are unavailable: "tôkyô".
+    Circumflex,
+    /// Drop the length entirely, as in passports: "tokyo".
+    Omit,
+    /// Passport alternative: long o becomes "oh", other long vowels are dropped: "tohkyoh".
+    Oh,
+    /// Long o becomes "ou", other long vowels are doubled: "toukyou", "su-pa-" -> "suupaa".
+    Ou,
+}
+
+/// Letter case of the romaji output.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Case {
+    /// "yamada tarou"
+    #[default]
+    Lower,
+    /// Capitalize the first letter of each whitespace-separated word: "Yamada Tarou".
+    ///
+    /// Whitespace is kept to separate the words, whatever the [`PassThrough`] policy. Each
+    /// conversion starts a word, even when appended to earlier output.
+    Title,
+    /// "YAMADA TAROU"
+    Upper,
+}
+
+/// What happens to characters that have no romaji, such as Latin letters, digits or Kanji.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum PassThrough {
+    /// Leave them out: "ABCカナ" -> "kana".
+    #[default]
+    Drop,
+    /// Copy them as is: "ABCカナ" -> "ABCkana".
+    Keep,
+    /// Write the given character instead of each: "ABCカナ" -> "???kana".
+    Placeholder(char),
+}
+
+/// How ン is separated from a following vowel or y.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum NSeparator {
+    /// Standard Hepburn, Kunrei and Nihon-shiki: キンエン -> "kin'en".
+    #[default]
+    Apostrophe,
+    /// As some styles write it: キンエン -> "kin-en".
+    Hyphen,
+    /// No separator, as in passports: キンエン -> "kinen".
+    Omit,
+}
+
+impl NSeparator {
+    fn as_char(self) -> Option<char> {
+        match self {
+            NSeparator::Apostrophe => Some('\''),
+            NSeparator::Hyphen => Some('-'),
+            NSeparator::Omit => None,
+        }
+    }
+}
+
+/// How the small tsu (ッ) is written.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub enum Sokuon {
+    /// Double the next consonant, writing っち as "tch" (カッパ -> "kappa", マッチャ -> "matcha").
+    #[default]
+    Double,
+    /// Double the first letter of the next syllable as is (マッチャ -> "maccha").
+    DoubleFirstLetter,
+    /// Write a fixed text instead, e.g. "q" or "xtu".
+    Literal(String),
+}
+
+static DOUBLE: Sokuon = Sokuon::Double;
+
+/// Everything `convert_kana_to_romaji` needs to know besides the input.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Rules<'a> {
+    pub(crate) system: System,
+    pub(crate) table: &'a Table,
+    pub(crate) long_vowel: LongVowel,
+    pub(crate) sokuon: &'a Sokuon,
+    /// Write ン as "m" before b, m and p.
+    pub(crate) m_before_labial: bool,
+    pub(crate) passport: bool,
+    pub(crate) case: Case,
+    pub(crate) pass_through: PassThrough,
+    pub(crate) n_separator: NSeparator,
+    /// Write standalone は, へ and を as "wa", "e" and "o".
+    pub(crate) phonetic_particles: bool,
+}
+
+impl Rules<'static> {
+    pub(crate) fn new(system: System) -> Self {
+        Rules {
+            system,
+            table: default_table(),
+            long_vowel: LongVowel::AsWritten,
+            sokuon: &DOUBLE,
+            m_before_labial: system == System::Hepburn,
+            passport: false,
+            case: Case::Lower,
+            pass_through: PassThrough::Drop,
+            n_separator: NSeparator::Apostrophe,
+            phonetic_particles: false,
+        }
+    }
+
+    fn hepburn_with(options: &HepburnOptions) -> Self {
+        Rules {
+            long_vowel: options.long_vowel,
+            passport: options.passport_spellings,
+            case: options.case,
+            pass_through: options.pass_through,
+            n_separator: options.n_separator,
+            phonetic_particles: options.phonetic_particles,
+            ..Rules::new(System::Hepburn)
+        }
+    }
+}
+
+/// Reusable Kana to Romaji converter that writes into existing buffers.
+///
+/// The converter keeps its working buffers between calls, so converting many strings
+/// does not allocate once the buffers have grown to the largest input.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::{KanaToRomajiConverter, System};
+///
+/// let mut converter = KanaToRomajiConverter::new(System::Hepburn);
+/// let mut out = String::new();
+/// for word in ["シンブン", "ざっし"] {
+///     converter.convert_into(word, &mut out);
+///     out.push('\n');
+/// }
+/// assert_eq!(out, "shimbun\nzasshi\n");
+///
+/// let mut bytes: Vec<u8> = Vec::new();
+/// converter.write_io("カタカナ", &mut bytes).unwrap();
+/// assert_eq!(bytes, b"katakana");
+/// ```
+#[derive(Debug, Clone)]
+pub struct KanaToRomajiConverter<'a> {
+    rules: Rules<'a>,
+    chars: Vec<char>,
+    offsets: Vec<usize>,
+    buffer: String,
+}
+
+impl KanaToRomajiConverter<'static> {
+    /// Converter for `system`, same as [`kana_to_romaji`].
+    pub fn new(system: System) -> Self {
+        KanaToRomajiConverter::from_rules(Rules::new(system))
+    }
+
+    /// Converter for Hepburn with options, same as [`kana_to_romaji_hepburn_with`].
+    pub fn hepburn_with(options: &HepburnOptions) -> Self {
+        KanaToRomajiConverter::from_rules(Rules::hepburn_with(options))
+    }
+}
+
+impl<'a> KanaToRomajiConverter<'a> {
+    /// Converter using a custom [`Table`], same as [`kana_to_romaji_with_table`].
+    pub fn with_table(system: System, table: &'a Table) -> Self {
+        KanaToRomajiConverter::from_rules(Rules {
+            table,
+            ..Rules::new(system)
+        })
+    }
+
+    pub(crate) fn from_rules(rules: Rules<'a>) -> Self {
+        KanaToRomajiConverter {
+            rules,
+            chars: Vec::new(),
+            offsets: Vec::new(),
+            buffer: String::new(),
+        }
+    }
+
+    /// Convert `input` into a new `String`.
+    pub fn convert(&mut self, input: &str) -> String {
+        let mut result = String::new();
+        self.convert_into(input, &mut result);
+        result
+    }
+
+    /// Append the romaji for `input` to `out`.
+    pub fn convert_into(&mut self, input: &str, out: &mut String) {
+        self.chars.clear();
+        self.offsets.clear();
+        fold_kana(input, &mut self.chars, &mut self.offsets);
+        write_kana_to_romaji(
+            input,
+            &self.chars,
+            &self.offsets,
+            self.rules,
+            out,
+            None,
+            None,
+        );
+    }
+
+    /// Write the romaji for `input` to a [`fmt::Write`] sink.
+    pub fn write_to<W: fmt::Write>(&mut self, input: &str, out: &mut W) -> fmt::Result {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        self.convert_into(input, &mut buffer);
+        let result = out.write_str(&buffer);
+        self.buffer = buffer;
+        result
+    }
+
+    /// Write the romaji for `input` as UTF-8 to an [`io::Write`] sink.
+    pub fn write_io<W: io::Write>(&mut self, input: &str, out: &mut W) -> io::Result<()> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        self.convert_into(input, &mut buffer);
+        let result = out.write_all(buffer.as_bytes());
+        self.buffer = buffer;
+        result
+    }
+}
+
+pub(crate) fn convert_kana_to_romaji(input: &str, rules: Rules) -> String {
+    let mut chars = Vec::new();
+    let mut offsets = Vec::new();
+    fold_kana(input, &mut chars, &mut offsets);
+    let mut result = String::with_capacity(input.len());
+    write_kana_to_romaji(input, &chars, &offsets, rules, &mut result, None, None);
+    result
+}
+
+pub(crate) fn convert_kana_to_romaji_aligned(input: &str, rules: Rules) -> (String, Alignment) {
+    let mut chars = Vec::new();
+    let mut offsets = Vec::new();
+    fold_kana(input, &mut chars, &mut offsets);
+    let mut result = String::with_capacity(input.len());
+    let mut spans = Vec::new();
+    write_kana_to_romaji(
+        input,
+        &chars,
+        &offsets,
+        rules,
+        &mut result,
+        Some(&mut spans),
+        None,
+    );
+    (result, Alignment::new(spans))
+}
+
+/// Append the chars of `input` to `chars` the way the table is keyed: kana followed by a
+/// (semi-)voiced sound mark composed into one char, and Hiragana folded into Katakana.
+///
+/// Also append the byte offset in `input` of each char to `offsets`, followed by
+/// `input.len()`.
+fn fold_kana(input: &str, chars: &mut Vec<char>, offsets: &mut Vec<usize>) {
+    // The last char as written, before folding, to compose a following mark with.
+    let mut last = None;
+    for (i, c) in input.char_indices() {
+        if let Some(composed) = last.and_then(|base| compose_mark(base, c))
+            && let Some(folded) = chars.last_mut()
+        {
+            *folded = fold_to_table_key(composed);
+            last = Some(composed);
+            continue;
+        }
+        chars.push(fold_to_table_key(c));
+        offsets.push(i);
+        last = Some(c);
+    }
+    offsets.push(input.len());
+}
+
+/// Append the romaji for `input`, folded by [`fold_kana`] into `chars` and `offsets`,
+/// to `result`.
+///
+/// With `spans`, also record which bytes of `input` produced which bytes of `result`.
+/// With `warnings`, also record the chars that have no romaji.
+fn write_kana_to_romaji(
+    input: &str,
+    chars: &[char],
+    offsets: &[usize],
+    rules: Rules,
+    result: &mut String,
+    mut spans: Option<&mut Vec<Span>>,
+    mut warnings: Option<&mut Vec<KanaError>>,
+) {
+    // The byte offsets of the particles, in order
+    let particles: Vec<usize> = if rules.phonetic_particles {
+        word_ranges(input)
+            .into_iter()
+            .filter(|word| particle_romaji(&input[word.clone()]).is_some())
+            .map(|word| word.start)
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let call_start = result.len();
+    let mut i = 0;
     while i < chars.len() {
-        // Check for combination (current + next)
-        if i + 1 < chars.len() {
-            if let Some(romaji) = get_combo_romaji(chars[i], chars[i + 1], system) {
-                result.push_str(romaji);
-                i += 2;
-                continue;
+        // The char as written, not as folded into Katakana
+        let source = &input[offsets[i]..offsets[i + 1]];
+        if is_unknown(chars[i], rules)
+            && !chars[i].is_whitespace()
+            && let Some(warnings) = warnings.as_deref_mut()
+        {
+            let mut written = source.chars();
+            let first = written.next().unwrap_or(chars[i]);
+            warnings.push(KanaError::UnknownChar {
+                character: written
+                    .next()
+                    .and_then(|mark| compose_mark(first, mark))
+                    .unwrap_or(first),
+                position: offsets[i],
+            });
+        }
+        let output_start = result.len();
+        let consumed = if let Some(romaji) = particle_romaji(source)
+            && particles.binary_search(&offsets[i]).is_ok()
+        {
+            // ン before "o" or "e" is separated as before any vowel: ごはんを -> "gohan'o"
+            if i > 0
+                && chars[i - 1] == 'ン'
+                && !romaji.starts_with('w')
+                && let Some(separator) = rules.n_separator.as_char()
+            {
+                result.push(separator);
             }
+            result.push_str(romaji);
+            1
+        } else {
+            write_next_romaji(chars, i, source, rules, result)
+        };
+        apply_case(result, call_start, output_start, rules.case);
+        if let Some(spans) = spans.as_deref_mut() {
+            spans.push(Span {
+                source: offsets[i]..offsets[i + consumed],
+                output: output_start..result.len(),
+            });
         }
+        i += consumed;
+    }
+}
 
-        // Check for small tsu (ッ)
-        if chars[i] == 'ッ' {
-            if i + 1 < chars.len() {
-                // Resolve the next romaji to find its first consonant
-                let (next_romaji, _) = resolve_next_romaji(&chars[i + 1..], system);
-                if let Some(first_char) = next_romaji.chars().next() {
-                    // Only double if it's a consonant.
-                    match (system, next_romaji.as_str()) {
-                        (System::Hepburn, s) if s.starts_with("ch") => result.push('t'),
-                        (_, _) if is_consonant(first_char) => result.push(first_char),
-                        _ => {} // atomic small tsu? or ignore
-                    }
-
-                    i += 1;
-                    continue;
+/// Rewrite `result[start..]` in `case`, where this call's output began at `call_start`.
+///
+/// Upper-casing can change the byte length (ǰ -> J̌), so this runs on each piece of
+/// output before its span is recorded. Title case starts a word at `call_start` and after
+/// whitespace, not after what `result` held before the call.
+fn apply_case(result: &mut String, call_start: usize, start: usize, case: Case) {
+    let converted: String = match case {
+        Case::Lower => return,
+        Case::Upper => result[start..]
+            .chars()
+            .flat_map(char::to_uppercase)
+            .collect(),
+        Case::Title => {
+            let mut word_start =
+                start == call_start || result[call_start..start].ends_with(char::is_whitespace);
+            let mut converted = String::with_capacity(result.len() - start);
+            for c in result[start..].chars() {
+                if word_start && c.is_alphabetic() {
+                    converted.extend(c.to_uppercase());
+                    word_start = false;
+                } else {
+                    converted.push(c);
+                    word_start |= c.is_whitespace();
                 }
             }
+            converted
         }
+    };
+    result.truncate(start);
+    result.push_str(&converted);
+}
 
-        // Check for 'n' (ン) special case in Hepburn
-        if chars[i] == 'ン' {
-            if let System::Hepburn = system {
-                if i + 1 < chars.len() {
-                    let (next_romaji, _) = resolve_next_romaji(&chars[i + 1..], system);
-                    if next_romaji.starts_with('b')
-                        || next_romaji.starts_with('m')
-                        || next_romaji.starts_with('p')
-                    {
-                        result.push('m');
-                        i += 1;
-                        continue;
-                    }
-                }
+/// Append the romaji for the kana starting at `chars[i]`, written as `source` in the input.
+///
+/// Returns the number of kana consumed.
+fn write_next_romaji(
+    chars: &[char],
+    i: usize,
+    source: &str,
+    rules: Rules,
+    result: &mut String,
+) -> usize {
+    // Check for combination (current + next)
+    if i + 1 < chars.len()
+        && let Some(romaji) = combo_romaji(chars[i], chars[i + 1], rules)
+    {
+        result.push_str(romaji);
+        return 2 + apply_long_vowel(&chars[i + 2..], result, rules);
+    }
+
+    // Check for small tsu (ッ)
+    if chars[i] == 'ッ'
+        && let Sokuon::Literal(text) = rules.sokuon
+    {
+        result.push_str(text);
+        return 1;
+    }
+    if chars[i] == 'ッ' && i + 1 < chars.len() {
+        // Resolve the next romaji to find its first consonant
+        let (next_romaji, _) = resolve_next_romaji(&chars[i + 1..], rules);
+        if let Some(first_char) = next_romaji.chars().next() {
+            // Only double if it's a consonant.
+            match (rules.sokuon, next_romaji) {
+                (Sokuon::Double, s) if s.starts_with("ch") => result.push('t'),
+                (_, _) if is_consonant(first_char) => result.push(first_char),
+                _ => {} // atomic small tsu? or ignore
             }
+            return 1;
         }
+    }
 
-        // Single char
-        let romaji = get_single_romaji(chars[i], system);
-        result.push_str(romaji);
-        i += 1;
+    // Check for 'n' (ン) special case in Hepburn
+    if chars[i] == 'ン' && rules.m_before_labial && i + 1 < chars.len() {
+        let (next_romaji, _) = resolve_next_romaji(&chars[i + 1..], rules);
+        if next_romaji.starts_with('b')
+            || next_romaji.starts_with('m')
+            || next_romaji.starts_with('p')
+        {
+            result.push('m');
+            return 1;
+        }
     }
-    result
+
+    // Separate ン from a following vowel or y (kin'en), so it does not read as キネン.
+    // Nihon-shiki separates ンン too, so that the output reads back unambiguously.
+    if chars[i] == 'ン' && i + 1 < chars.len() {
+        let (next_romaji, _) = resolve_next_romaji(&chars[i + 1..], rules);
+        if let Some(separator) = rules.n_separator.as_char()
+            && (next_romaji.starts_with(['a', 'i', 'u', 'e', 'o', 'y'])
+                || (rules.system == System::NihonShiki && chars[i + 1] == 'ン'))
+        {
+            result.push('n');
+            result.push(separator);
+            return 1;
+        }
+    }
+
+    // Characters with no romaji follow the pass-through policy
+    if is_unknown(chars[i], rules) {
+        // Title case needs the spaces between words to tell where they start
+        if rules.case == Case::Title && chars[i].is_whitespace() {
+            result.push_str(source);
+            return 1;
+        }
+        match rules.pass_through {
+            PassThrough::Drop => {}
+            PassThrough::Keep => result.push_str(source),
+            PassThrough::Placeholder(placeholder) => result.push(placeholder),
+        }
+        return 1;
+    }
+
+    // Single char
+    let romaji = single_romaji(chars[i], rules);
+    result.push_str(romaji);
+    if romaji.is_empty() {
+        return 1;
+    }
+    1 + apply_long_vowel(&chars[i + 1..], result, rules)
 }
 
-fn resolve_next_romaji(chars: &[char], system: System) -> (String, usize) {
+/// Whether `c` has no romaji. ッ has none of its own but is not unknown.
+fn is_unknown(c: char, rules: Rules) -> bool {
+    c != 'ッ' && single_romaji(c, rules).is_empty()
+}
+
+/// Rewrite the vowel at the end of `result` if the following kana lengthen it.
+///
+/// Returns the number of kana consumed from `rest`.
+fn apply_long_vowel(rest: &[char], result: &mut String, rules: Rules) -> usize {
+    if rules.long_vowel == LongVowel::AsWritten {
+        return 0;
+    }
+    let Some(vowel) = result.chars().last().filter(|c| "aiueo".contains(*c)) else {
+        return 0;
+    };
+
+    // Any run of ー merges, but only one ウ or オ: in おおうち or ほうおう the next one
+    // is a vowel of its own.
+    let mut consumed = 0;
+    let mut vowel_kana = false;
+    while consumed < rest.len() {
+        let lengthens = match rest[consumed] {
+            'ー' => true,
+            'ウ' => !vowel_kana && (vowel == 'o' || vowel == 'u'),
+            'オ' => !vowel_kana && vowel == 'o',
+            _ => false,
+        };
+        // ウィ, ウェ, ... start a new syllable rather than lengthening the vowel.
+        let starts_combo = consumed + 1 < rest.len()
+            && combo_romaji(rest[consumed], rest[consumed + 1], rules).is_some();
+        if !lengthens || starts_combo {
+            break;
+        }
+        vowel_kana |= rest[consumed] != 'ー';
+        consumed += 1;
+    }
+    if consumed == 0 {
+        return 0;
+    }
+
+    result.pop();
+    match rules.long_vowel {
+        LongVowel::AsWritten => unreachable!(),
+        LongVowel::Macron => result.push(match vowel {
+            'a' => 'ā',
+            'i' => 'ī',
+            'u' => 'ū',
+            'e' => 'ē',
+            _ => 'ō',
+        }),
+        LongVowel::Circumflex => result.push(match vowel {
+            'a' => 'â',
+            'i' => 'î',
+            'u' => 'û',
+            'e' => 'ê',
+            _ => 'ô',
+        }),
+        LongVowel::Omit => result.push(vowel),
+        LongVowel::Oh => {
+            result.push(vowel);
+            if vowel == 'o' {
+                result.push('h');
+            }
+        }
+        LongVowel::Ou => {
+            result.push(vowel);
+            result.push(if vowel == 'o' { 'u' } else { vowel });
+        }
+    }
+    consumed
+}
+
+fn resolve_next_romaji<'a>(chars: &[char], rules: Rules<'a>) -> (&'a str, usize) {
     if chars.is_empty() {
-        return (String::new(), 0);
+        return ("", 0);
+    }
+    if chars.len() >= 2
+        && let Some(romaji) = combo_romaji(chars[0], chars[1], rules)
+    {
+        return (romaji, 2);
     }
-    if chars.len() >= 2 {
-        if let Some(romaji) = get_combo_romaji(chars[0], chars[1], system) {
-            return (romaji.to_string(), 2);
+    (single_romaji(chars[0], rules), 1)
+}
+
+fn single_romaji<'a>(c: char, rules: Rules<'a>) -> &'a str {
+    match c {
+        'ヲ' if rules.passport => "o",
+        'ヴ' if rules.passport => "bu",
+        _ => lookup(&[c], rules).unwrap_or(""), // Fallback
+    }
+}
+
+fn combo_romaji<'a>(c1: char, c2: char, rules: Rules<'a>) -> Option<&'a str> {
+    // The passport table only lists the contracted sounds written with ャ, ュ and ョ,
+    // and ヴァ, ヴィ, ヴェ and ヴォ, which it writes with a b like ヴ.
+    if rules.passport {
+        match (c1, c2) {
+            ('ヴ', 'ァ') => return Some("ba"),
+            ('ヴ', 'ィ') => return Some("bi"),
+            ('ヴ', 'ェ') => return Some("be"),
+            ('ヴ', 'ォ') => return Some("bo"),
+            (_, 'ャ' | 'ュ' | 'ョ') => {}
+            _ => return None,
         }
     }
-    (get_single_romaji(chars[0], system).to_string(), 1)
+    lookup(&[c1, c2], rules)
+}
+
+fn lookup<'a>(kana: &[char], rules: Rules<'a>) -> Option<&'a str> {
+    // Table keys are at most two chars, so build the key on the stack.
+    let mut buf = [0u8; 8];
+    let mut len = 0;
+    for c in kana {
+        len += c.encode_utf8(&mut buf[len..]).len();
+    }
+    let key = std::str::from_utf8(&buf[..len]).ok()?;
+    rules.table.romaji(key, rules.system)
+}
+
+/// Fold a char into the Katakana the table is keyed on.
+///
+/// Unlike [`hiragana_to_katakana`](crate::core::kana::hiragana_to_katakana), this works
+/// on single chars and leaves some Hiragana unfolded.
+fn fold_to_table_key(c: char) -> char {
+    match c {
+        // ゕ, ゖ and the iteration marks are left as is, so that table entries for
+        // ヵ, ヶ, ヽ and ヾ do not apply to them.
+        'ゕ' | 'ゖ' | 'ゝ' | 'ゞ' => c,
+        _ => to_katakana(c),
+    }
+}
+
+/// The pronunciation of a particle as written in Hiragana. Katakana ハ, ヘ and ヲ are never
+/// particles.
+fn particle_romaji(word: &str) -> Option<&'static str> {
+    match word {
+        "は" => Some("wa"),
+        "へ" => Some("e"),
+        "を" => Some("o"),
+        _ => None,
+    }
 }
 
 fn is_consonant(c: char) -> bool {
@@ -131,290 +938,6 @@ fn is_consonant(c: char) -> bool {
     )
 }
 
-fn get_single_romaji(c: char, system: System) -> &'static str {
-    match system {
-        System::Hepburn => match c {
-            'ア' => "a",
-            'イ' => "i",
-            'ウ' => "u",
-            'エ' => "e",
-            'オ' => "o",
-            'カ' => "ka",
-            'キ' => "ki",
-            'ク' => "ku",
-            'ケ' => "ke",
-            'コ' => "ko",
-            'サ' => "sa",
-            'シ' => "shi",
-            'ス' => "su",
-            'セ' => "se",
-            'ソ' => "so",
-            'タ' => "ta",
-            'チ' => "chi",
-            'ツ' => "tsu",
-            'テ' => "te",
-            'ト' => "to",
-            'ナ' => "na",
-            'ニ' => "ni",
-            'ヌ' => "nu",
-            'ネ' => "ne",
-            'ノ' => "no",
-            'ハ' => "ha",
-            'ヒ' => "hi",
-            'フ' => "fu",
-            'ヘ' => "he",
-            'ホ' => "ho",
-            'マ' => "ma",
-            'ミ' => "mi",
-            'ム' => "mu",
-            'メ' => "me",
-            'モ' => "mo",
-            'ヤ' => "ya",
-            'ユ' => "yu",
-            'ヨ' => "yo",
-            'ラ' => "ra",
-            'リ' => "ri",
-            'ル' => "ru",
-            'レ' => "re",
-            'ロ' => "ro",
-            'ワ' => "wa",
-            'ヲ' => "wo",
-            'ン' => "n",
-            'ガ' => "ga",
-            'ギ' => "gi",
-            'グ' => "gu",
-            'ゲ' => "ge",
-            'ゴ' => "go",
-            'ザ' => "za",
-            'ジ' => "ji",
-            'ズ' => "zu",
-            'ゼ' => "ze",
-            'ゾ' => "zo",
-            'ダ' => "da",
-            'ヂ' => "ji",
-            'ヅ' => "zu",
-            'デ' => "de",
-            'ド' => "do",
-            'バ' => "ba",
-            'ビ' => "bi",
-            'ブ' => "bu",
-            'ベ' => "be",
-            'ボ' => "bo",
-            'パ' => "pa",
-            'ピ' => "pi",
-            'プ' => "pu",
-            'ペ' => "pe",
-            'ポ' => "po",
-            'ァ' => "a",
-            'ィ' => "i",
-            'ゥ' => "u",
-            'ェ' => "e",
-            'ォ' => "o",
-            'ャ' => "ya",
-            'ュ' => "yu",
-            'ョ' => "yo",
-            'ヮ' => "wa",
-            'ー' => "-",
-            _ => "", // Fallback
-        },
-        System::Kunrei => match c {
-            'ア' => "a",
-            'イ' => "i",
-            'ウ' => "u",
-            'エ' => "e",
-            'オ' => "o",
-            'カ' => "ka",
-            'キ' => "ki",
-            'ク' => "ku",
-            'ケ' => "ke",
-            'コ' => "ko",
-            'サ' => "sa",
-            'シ' => "si",
-            'ス' => "su",
-            'セ' => "se",
-            'ソ' => "so",
-            'タ' => "ta",
-            'チ' => "ti",
-            'ツ' => "tu",
-            'テ' => "te",
-            'ト' => "to",
-            'ナ' => "na",
-            'ニ' => "ni",
-            'ヌ' => "nu",
-            'ネ' => "ne",
-            'ノ' => "no",
-            'ハ' => "ha",
-            'ヒ' => "hi",
-            'フ' => "hu",
-            'ヘ' => "he",
-            'ホ' => "ho",
-            'マ' => "ma",
-            'ミ' => "mi",
-            'ム' => "mu",
-            'メ' => "me",
-            'モ' => "mo",
-            'ヤ' => "ya",
-            'ユ' => "yu",
-            'ヨ' => "yo",
-            'ラ' => "ra",
-            'リ' => "ri",
-            'ル' => "ru",
-            'レ' => "re",
-            'ロ' => "ro",
-            'ワ' => "wa",
-            'ヲ' => "wo",
-            'ン' => "n",
-            'ガ' => "ga",
-            'ギ' => "gi",
-            'グ' => "gu",
-            'ゲ' => "ge",
-            'ゴ' => "go",
-            'ザ' => "za",
-            'ジ' => "zi",
-            'ズ' => "zu",
-            'ゼ' => "ze",
-            'ゾ' => "zo",
-            'ダ' => "da",
-            'ヂ' => "zi",
-            'ヅ' => "zu",
-            'デ' => "de",
-            'ド' => "do",
-            'バ' => "ba",
-            'ビ' => "bi",
-            'ブ' => "bu",
-            'ベ' => "be",
-            'ボ' => "bo",
-            'パ' => "pa",
-            'ピ' => "pi",
-            'プ' => "pu",
-            'ペ' => "pe",
-            'ポ' => "po",
-            'ァ' => "a",
-            'ィ' => "i",
-            'ゥ' => "u",
-            'ェ' => "e",
-            'ォ' => "o",
-            'ャ' => "ya",
-            'ュ' => "yu",
-            'ョ' => "yo",
-            'ヮ' => "wa",
-            'ー' => "-",
-            _ => "",
-        },
-    }
-}
-
-fn get_combo_romaji(c1: char, c2: char, system: System) -> Option<&'static str> {
-    match system {
-        System::Hepburn => match (c1, c2) {
-            ('キ', 'ャ') => Some("kya"),
-            ('キ', 'ュ') => Some("kyu"),
-            ('キ', 'ョ') => Some("kyo"),
-            ('シ', 'ャ') => Some("sha"),
-            ('シ', 'ュ') => Some("shu"),
-            ('シ', 'ョ') => Some("sho"),
-            ('チ', 'ャ') => Some("cha"),
-            ('チ', 'ュ') => Some("chu"),
-            ('チ', 'ョ') => Some("cho"),
-            ('ニ', 'ャ') => Some("nya"),
-            ('ニ', 'ュ') => Some("nyu"),
-            ('ニ', 'ョ') => Some("nyo"),
-            ('ヒ', 'ャ') => Some("hya"),
-            ('ヒ', 'ュ') => Some("hyu"),
-            ('ヒ', 'ョ') => Some("hyo"),
-            ('ミ', 'ャ') => Some("mya"),
-            ('ミ', 'ュ') => Some("myu"),
-            ('ミ', 'ョ') => Some("myo"),
-            ('リ', 'ャ') => Some("rya"),
-            ('リ', 'ュ') => Some("ryu"),
-            ('リ', 'ョ') => Some("ryo"),
-            ('ギ', 'ャ') => Some("gya"),
-            ('ギ', 'ュ') => Some("gyu"),
-            ('ギ', 'ョ') => Some("gyo"),
-            ('ジ', 'ャ') => Some("ja"),
-            ('ジ', 'ュ') => Some("ju"),
-            ('ジ', 'ョ') => Some("jo"),
-            ('ビ', 'ャ') => Some("bya"),
-            ('ビ', 'ュ') => Some("byu"),
-            ('ビ', 'ョ') => Some("byo"),
-            ('ピ', 'ャ') => Some("pya"),
-            ('ピ', 'ュ') => Some("pyu"),
-            ('ピ', 'ョ') => Some("pyo"),
-            ('ヂ', 'ャ') => Some("ja"),
-            ('ヂ', 'ュ') => Some("ju"),
-            ('ヂ', 'ョ') => Some("jo"),
-            ('テ', 'ィ') => Some("ti"),
-            ('デ', 'ィ') => Some("di"),
-            ('ト', 'ゥ') => Some("tu"),
-            ('ド', 'ゥ') => Some("du"),
-            ('フ', 'ァ') => Some("fa"),
-            ('フ', 'ィ') => Some("fi"),
-            ('フ', 'ェ') => Some("fe"),
-            ('フ', 'ォ') => Some("fo"),
-            ('ウ', 'ィ') => Some("wi"),
-            ('ウ', 'ェ') => Some("we"),
-            ('ウ', 'ォ') => Some("wo"),
-            ('シ', 'ェ') => Some("she"),
-            ('ジ', 'ェ') => Some("je"),
-            ('チ', 'ェ') => Some("che"),
-            _ => None,
-        },
-        System::Kunrei => match (c1, c2) {
-            ('キ', 'ャ') => Some("kya"),
-            ('キ', 'ュ') => Some("kyu"),
-            ('キ', 'ョ') => Some("kyo"),
-            ('シ', 'ャ') => Some("sya"),
-            ('シ', 'ュ') => Some("syu"),
-            ('シ', 'ョ') => Some("syo"),
-            ('チ', 'ャ') => Some("tya"),
-            ('チ', 'ュ') => Some("tyu"),
-            ('チ', 'ョ') => Some("tyo"),
-            ('ニ', 'ャ') => Some("nya"),
-            ('ニ', 'ュ') => Some("nyu"),
-            ('ニ', 'ョ') => Some("nyo"),
-            ('ヒ', 'ャ') => Some("hya"),
-            ('ヒ', 'ュ') => Some("hyu"),
-            ('ヒ', 'ョ') => Some("hyo"),
-            ('ミ', 'ャ') => Some("mya"),
-            ('ミ', 'ュ') => Some("myu"),
-            ('ミ', 'ョ') => Some("myo"),
-            ('リ', 'ャ') => Some("rya"),
-            ('リ', 'ュ') => Some("ryu"),
-            ('リ', 'ョ') => Some("ryo"),
-            ('ギ', 'ャ') => Some("gya"),
-            ('ギ', 'ュ') => Some("gyu"),
-            ('ギ', 'ョ') => Some("gyo"),
-            ('ジ', 'ャ') => Some("zya"),
-            ('ジ', 'ュ') => Some("zyu"),
-            ('ジ', 'ョ') => Some("zyo"),
-            ('ビ', 'ャ') => Some("bya"),
-            ('ビ', 'ュ') => Some("byu"),
-            ('ビ', 'ョ') => Some("byo"),
-            ('ピ', 'ャ') => Some("pya"),
-            ('ピ', 'ュ') => Some("pyu"),
-            ('ピ', 'ョ') => Some("pyo"),
-            ('ヂ', 'ャ') => Some("zya"),
-            ('ヂ', 'ュ') => Some("zyu"),
-            ('ヂ', 'ョ') => Some("zyo"),
-            ('テ', 'ィ') => Some("ti"),
-            ('デ', 'ィ') => Some("di"),
-            ('ト', 'ゥ') => Some("tu"),
-            ('ド', 'ゥ') => Some("du"),
-            ('フ', 'ァ') => Some("fa"),
-            ('フ', 'ィ') => Some("fi"),
-            ('フ', 'ェ') => Some("fe"),
-            ('フ', 'ォ') => Some("fo"),
-            ('ウ', 'ィ') => Some("wi"),
-            ('ウ', 'ェ') => Some("we"),
-            ('ウ', 'ォ') => Some("wo"),
-            ('シ', 'ェ') => Some("sye"),
-            ('ジ', 'ェ') => Some("zye"),
-            ('チ', 'ェ') => Some("tye"),
-            _ => None,
-        },
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -465,7 +988,7 @@ mod tests {
         assert_eq!(kana_to_romaji_hepburn("サンマ"), "samma");
         assert_eq!(kana_to_romaji_hepburn("カンパイ"), "kampai");
         assert_eq!(kana_to_romaji_hepburn("アンナイ"), "annai");
-        assert_eq!(kana_to_romaji_hepburn("カンイ"), "kani");
+        assert_eq!(kana_to_romaji_hepburn("カンイ"), "kan'i");
 
         // Combo case
         assert_eq!(kana_to_romaji_hepburn("コンピュ"), "kompyu"); // 'ピュ' -> pyu (starts with p)
@@ -477,9 +1000,722 @@ mod tests {
         assert_eq!(kana_to_romaji_kunrei("サンマ"), "sanma");
         assert_eq!(kana_to_romaji_kunrei("カンパイ"), "kanpai");
         assert_eq!(kana_to_romaji_kunrei("アンナイ"), "annai");
-        assert_eq!(kana_to_romaji_kunrei("カンイ"), "kani");
+        assert_eq!(kana_to_romaji_kunrei("カンイ"), "kan'i");
 
         // Combo case
         assert_eq!(kana_to_romaji_kunrei("コンピュ"), "konpyu");
     }
+
+    #[test]
+    fn test_hiragana_input() {
+        assert_eq!(kana_to_romaji_hepburn("ひらがな"), "hiragana");
+        assert_eq!(kana_to_romaji_hepburn("がっこう"), "gakkou");
+        assert_eq!(kana_to_romaji_hepburn("まっちゃ"), "matcha");
+        assert_eq!(kana_to_romaji_hepburn("しんぶん"), "shimbun");
+        assert_eq!(kana_to_romaji_hepburn("きょうと"), "kyouto");
+
+        assert_eq!(kana_to_romaji_kunrei("しんぶん"), "sinbun");
+        assert_eq!(kana_to_romaji_kunrei("ちゃわん"), "tyawan");
+    }
+
+    #[test]
+    fn test_mixed_scripts() {
+        // Hiragana and Katakana romanize identically, even when mixed.
+        assert_eq!(
+            kana_to_romaji_hepburn("とうきょうタワー"),
+            kana_to_romaji_hepburn("トウキョウタワー")
+        );
+        assert_eq!(kana_to_romaji_hepburn("ぎゅうニュウ"), "gyuunyuu");
+        assert_eq!(kana_to_romaji_hepburn("きャ"), "kya");
+    }
+
+    #[test]
+    fn test_nihon_shiki() {
+        assert_eq!(kana_to_romaji_nihon_shiki("カタカナ"), "katakana");
+        assert_eq!(kana_to_romaji_nihon_shiki("シャシン"), "syasin");
+        assert_eq!(kana_to_romaji_nihon_shiki("ハナヂ"), "hanadi");
+        assert_eq!(kana_to_romaji_nihon_shiki("ミカヅキ"), "mikaduki");
+        assert_eq!(kana_to_romaji_nihon_shiki("ヂャ"), "dya");
+        assert_eq!(kana_to_romaji_nihon_shiki("ヲ"), "wo");
+        assert_eq!(kana_to_romaji_nihon_shiki("クヮシ"), "kwasi");
+        assert_eq!(kana_to_romaji_nihon_shiki("カッパ"), "kappa");
+        assert_eq!(kana_to_romaji_nihon_shiki("コンピュ"), "konpyu");
+        assert_eq!(kana_to_romaji_nihon_shiki("キンエン"), "kin'en");
+        assert_eq!(kana_to_romaji_nihon_shiki("ホンヤ"), "hon'ya");
+        assert_eq!(kana_to_romaji_nihon_shiki("アンナ"), "anna");
+        assert_eq!(kana_to_romaji_nihon_shiki("ンン"), "n'n");
+
+        // Kunrei merges the same kana
+        assert_eq!(kana_to_romaji_kunrei("ハナヂ"), "hanazi");
+        assert_eq!(kana_to_romaji_kunrei("ミカヅキ"), "mikazuki");
+    }
+
+    #[test]
+    fn test_runtime_system() {
+        for (system, expected) in [
+            (System::Hepburn, "tsuzuki"),
+            (System::Kunrei, "tuzuki"),
+            (System::NihonShiki, "tuduki"),
+        ] {
+            assert_eq!(kana_to_romaji("ツヅキ", system), expected);
+        }
+        assert_eq!(
+            kana_to_romaji("シンブン", System::Hepburn),
+            kana_to_romaji_hepburn("シンブン")
+        );
+    }
+
+    #[test]
+    fn test_long_vowel_policies() {
+        let with = |input: &str, long_vowel: LongVowel| {
+            kana_to_romaji_hepburn_with(
+                input,
+                &HepburnOptions {
+                    long_vowel,
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert_eq!(with("トーキョー", LongVowel::AsWritten), "to-kyo-");
+        assert_eq!(with("トウキョウ", LongVowel::AsWritten), "toukyou");
+
+        assert_eq!(with("トーキョー", LongVowel::Macron), "tōkyō");
+        assert_eq!(with("とうきょう", LongVowel::Macron), "tōkyō");
+        assert_eq!(with("パーティー", LongVowel::Macron), "pātī");
+        assert_eq!(with("すうがく", LongVowel::Macron), "sūgaku");
+        assert_eq!(with("おおさか", LongVowel::Macron), "ōsaka");
+
+        assert_eq!(with("トーキョー", LongVowel::Circumflex), "tôkyô");
+        assert_eq!(with("きゅうしゅう", LongVowel::Circumflex), "kyûshû");
+
+        assert_eq!(with("とうきょう", LongVowel::Omit), "tokyo");
+        assert_eq!(with("ゆうこ", LongVowel::Omit), "yuko");
+
+        assert_eq!(with("おおの", LongVowel::Oh), "ohno");
+        assert_eq!(with("ゆうこ", LongVowel::Oh), "yuko");
+
+        assert_eq!(with("トーキョー", LongVowel::Ou), "toukyou");
+        assert_eq!(with("スーパー", LongVowel::Ou), "suupaa");
+    }
+
+    #[test]
+    fn test_long_vowel_boundaries() {
+        let macron = HepburnOptions {
+            long_vowel: LongVowel::Macron,
+            ..Default::default()
+        };
+        // えい and いい are not merged
+        assert_eq!(kana_to_romaji_hepburn_with("せんせい", &macron), "sensei");
+        assert_eq!(kana_to_romaji_hepburn_with("にいがた", &macron), "niigata");
+        // ウィ starts a new syllable
+        assert_eq!(kana_to_romaji_hepburn_with("ソウィ", &macron), "sowi");
+        // Repeated marks still make one long vowel
+        assert_eq!(kana_to_romaji_hepburn_with("ゴーール", &macron), "gōru");
+        // Nothing to lengthen
+        assert_eq!(kana_to_romaji_hepburn_with("ンー", &macron), "n-");
+        // One vowel kana lengthens; the next is a vowel of its own
+        assert_eq!(kana_to_romaji_hepburn_with("おおうち", &macron), "ōuchi");
+        assert_eq!(kana_to_romaji_hepburn_with("ごうう", &macron), "gōu");
+        assert_eq!(kana_to_romaji_hepburn_with("ほうおう", &macron), "hōō");
+        assert_eq!(kana_to_romaji_hepburn_with("こうおつ", &macron), "kōotsu");
+        assert_eq!(kana_to_romaji_hepburn_with("こうー", &macron), "kō");
+    }
+
+    #[test]
+    fn test_case() {
+        let title = HepburnOptions {
+            case: Case::Title,
+            ..Default::default()
+        };
+        let upper = HepburnOptions {
+            case: Case::Upper,
+            ..HepburnOptions::passport()
+        };
+        assert_eq!(
+            kana_to_romaji_hepburn_with("ヤマダタロウ", &title),
+            "Yamadatarou"
+        );
+        // Whitespace separates words even though it is otherwise dropped
+        assert_eq!(
+            kana_to_romaji_hepburn_with("ヤマダ タロウ", &title),
+            "Yamada Tarou"
+        );
+        assert_eq!(
+            kana_to_romaji_hepburn_with("ヤマダ\u{3000}タロウ", &title),
+            "Yamada\u{3000}Tarou"
+        );
+        assert_eq!(
+            kana_to_romaji_hepburn_with("ヤマダ タロウ", &HepburnOptions::default()),
+            "yamadatarou"
+        );
+        assert_eq!(
+            crate::core::scheme::Scheme::builder(System::Hepburn)
+                .case(Case::Title)
+                .build()
+                .convert("やまだ たろう"),
+            "Yamada Tarou"
+        );
+        assert_eq!(
+            kana_to_romaji_hepburn_with("ヤマダタロウ", &upper),
+            "YAMADATARO"
+        );
+        assert_eq!(kana_to_romaji_hepburn_with("ちゃ", &title), "Cha");
+        assert_eq!(kana_to_romaji_hepburn_with("っちゃ", &title), "Tcha");
+
+        let macron_title = HepburnOptions {
+            long_vowel: LongVowel::Macron,
+            case: Case::Title,
+            ..Default::default()
+        };
+        assert_eq!(
+            kana_to_romaji_hepburn_with("オーサカ", &macron_title),
+            "Ōsaka"
+        );
+
+        // Title case starts words after whitespace only
+        let mut result = String::from("mr. ");
+        apply_case(&mut result, 0, 0, Case::Title);
+        assert_eq!(result, "Mr. ");
+        result.push_str("yamada tarou (taro)");
+        apply_case(&mut result, 0, 4, Case::Title);
+        assert_eq!(result, "Mr. Yamada Tarou (Taro)");
+        let mut result = String::from("xabc");
+        apply_case(&mut result, 0, 1, Case::Title);
+        assert_eq!(result, "xabc");
+        // A new call starts a word, whatever the buffer held before it
+        apply_case(&mut result, 1, 1, Case::Title);
+        assert_eq!(result, "xAbc");
+
+        let mut converter = KanaToRomajiConverter::hepburn_with(&title);
+        let mut out = String::new();
+        converter.convert_into("たろう", &mut out);
+        converter.convert_into("じろう", &mut out);
+        assert_eq!(out, "TarouJirou");
+        assert_eq!(converter.convert("じろう"), "Jirou");
+
+        // ǰ has no single upper-case letter, so the output grows; spans follow it
+        let upper_keep = Rules {
+            case: Case::Upper,
+            pass_through: PassThrough::Keep,
+            ..Rules::new(System::Hepburn)
+        };
+        let (romaji, alignment) = convert_kana_to_romaji_aligned("ǰカ", upper_keep);
+        assert_eq!(romaji, "J\u{30C}KA");
+        assert_eq!(alignment.output_range(2..5), 3..5);
+        assert_eq!(alignment.source_range(0..3), 0..2);
+    }
+
+    #[test]
+    fn test_n_separator() {
+        assert_eq!(kana_to_romaji_hepburn("キンエン"), "kin'en");
+        assert_eq!(kana_to_romaji_hepburn("ホンヤ"), "hon'ya");
+        assert_eq!(kana_to_romaji_hepburn("ホンヤク"), "hon'yaku");
+        assert_eq!(kana_to_romaji_kunrei("キンエン"), "kin'en");
+        assert_eq!(kana_to_romaji_kunrei("コンニャク"), "konnyaku");
+        // Only Nihon-shiki separates ンン
+        assert_eq!(kana_to_romaji_hepburn("アンナ"), "anna");
+        assert_eq!(kana_to_romaji_hepburn("ホンンア"), "honn'a");
+        assert_eq!(kana_to_romaji_nihon_shiki("ホンンア"), "hon'n'a");
+        // Not before the prolonged sound mark or at the end
+        assert_eq!(kana_to_romaji_hepburn("ンー"), "n-");
+        assert_eq!(kana_to_romaji_hepburn("ホン"), "hon");
+
+        let hyphen = HepburnOptions {
+            n_separator: NSeparator::Hyphen,
+            ..Default::default()
+        };
+        assert_eq!(kana_to_romaji_hepburn_with("キンエン", &hyphen), "kin-en");
+        assert_eq!(kana_to_romaji_passport("キンエン"), "kinen");
+        assert_eq!(kana_to_romaji_passport("ホンヤ"), "honya");
+    }
+
+    #[test]
+    fn test_phonetic_particles() {
+        let particles = HepburnOptions {
+            phonetic_particles: true,
+            pass_through: PassThrough::Keep,
+            ..Default::default()
+        };
+        assert_eq!(
+            kana_to_romaji_hepburn_with("わたし は がくせい です", &particles),
+            "watashi wa gakusei desu"
+        );
+        assert_eq!(
+            kana_to_romaji_hepburn_with("がっこう へ いく", &particles),
+            "gakkou e iku"
+        );
+        assert_eq!(
+            kana_to_romaji_hepburn_with("ほん を よむ", &particles),
+            "hon o yomu"
+        );
+        assert_eq!(
+            kana_to_romaji_hepburn_with("は、へ。を", &particles),
+            "wa、e。o"
+        );
+        // Inside words they are read as usual
+        assert_eq!(
+            kana_to_romaji_hepburn_with("はは へや をとこ", &particles),
+            "haha heya wotoko"
+        );
+        assert_eq!(
+            kana_to_romaji_hepburn_with("ごはんをたべる", &particles),
+            "gohan'otaberu"
+        );
+        // Unspaced text is segmented
+        assert_eq!(
+            kana_to_romaji_hepburn_with("わたしはがくせいです", &particles),
+            "watashiwagakuseidesu"
+        );
+        assert_eq!(
+            kana_to_romaji_hepburn_with("こんにちは", &particles),
+            "konnichiwa"
+        );
+        assert_eq!(
+            kana_to_romaji_hepburn_with("私は学生です", &particles),
+            "私wa学生desu"
+        );
+        // Only the Hiragana particles
+        assert_eq!(
+            kana_to_romaji_hepburn_with("ハ ヘ ヲ", &particles),
+            "ha he wo"
+        );
+        // Spaces still delimit particles when they are dropped from the output
+        let dropped = HepburnOptions {
+            phonetic_particles: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            kana_to_romaji_hepburn_with("わたし は がくせい です", &dropped),
+            "watashiwagakuseidesu"
+        );
+        // Off by default
+        assert_eq!(kana_to_romaji_hepburn("は"), "ha");
+    }
+
+    #[test]
+    fn test_punctuation() {
+        let table = Table::with_punctuation();
+        assert_eq!(
+            kana_to_romaji_with_table("「ジョン・スミス」！", System::Hepburn, &table),
+            "[jon/sumisu]!"
+        );
+        assert_eq!(
+            kana_to_romaji_with_table("ええ、ほんとう？", System::Kunrei, &table),
+            "ee,hontou?"
+        );
+        // Punctuation ends a syllable like any other kana
+        assert_eq!(
+            kana_to_romaji_with_table("ン。ア", System::NihonShiki, &table),
+            "n.a"
+        );
+        assert_eq!(kana_to_romaji_hepburn("ええ、そう。"), "eesou");
+    }
+
+    #[test]
+    fn test_pass_through() {
+        let keep = HepburnOptions {
+            pass_through: PassThrough::Keep,
+            ..Default::default()
+        };
+        let placeholder = HepburnOptions {
+            pass_through: PassThrough::Placeholder('?'),
+            ..Default::default()
+        };
+        let input = "ABCカタカナ123";
+        assert_eq!(kana_to_romaji_hepburn(input), "katakana");
+        assert_eq!(kana_to_romaji_hepburn_with(input, &keep), "ABCkatakana123");
+        assert_eq!(
+            kana_to_romaji_hepburn_with(input, &placeholder),
+            "???katakana???"
+        );
+
+        assert_eq!(
+            kana_to_romaji_hepburn_with("東京タワー", &keep),
+            "東京tawa-"
+        );
+        assert_eq!(
+            kana_to_romaji_hepburn_with("ヤマダ タロウ", &keep),
+            "yamada tarou"
+        );
+        // Kept as written: not folded into Katakana, marks not composed
+        assert_eq!(kana_to_romaji_hepburn_with("ゕゖゝゞ", &keep), "ゕゖゝゞ");
+        assert_eq!(
+            kana_to_romaji_hepburn_with("ワ\u{3099}イン", &keep),
+            "ワ\u{3099}in"
+        );
+        let mut table = Table::empty();
+        table.insert_kana("ア", "a", "a", "a");
+        let scheme = crate::core::scheme::Scheme::builder(System::Hepburn)
+            .table(table)
+            .pass_through(PassThrough::Keep)
+            .build();
+        assert_eq!(scheme.convert("ゔあ"), "ゔa");
+        assert_eq!(scheme.convert("う\u{3099}ア"), "う\u{3099}a");
+        // ッ is known even when it has nothing to double
+        assert_eq!(kana_to_romaji_hepburn_with("アッ!", &placeholder), "a?");
+
+        let title = HepburnOptions {
+            case: Case::Title,
+            ..keep
+        };
+        assert_eq!(
+            kana_to_romaji_hepburn_with("やまだ たろう", &title),
+            "Yamada Tarou"
+        );
+    }
+
+    #[test]
+    fn test_try_kana_to_romaji() {
+        assert_eq!(
+            try_kana_to_romaji("ガッコウ", System::Kunrei),
+            Ok("gakkou".to_string())
+        );
+        assert_eq!(try_kana_to_romaji("ッ", System::Hepburn), Ok(String::new()));
+        assert_eq!(
+            try_kana_to_romaji("カナ 1", System::Hepburn),
+            Err(KanaError::UnknownChar {
+                character: '1',
+                position: 7
+            })
+        );
+        // Whitespace is not an error, in either direction
+        assert_eq!(
+            try_kana_to_romaji("カナ\tかな\u{3000}", System::Hepburn),
+            Ok("kanakana".to_string())
+        );
+    }
+
+    #[test]
+    fn test_with_warnings() {
+        let (romaji, warnings) = kana_to_romaji_with_warnings("にほん語です。", System::Hepburn);
+        assert_eq!(romaji, kana_to_romaji_hepburn("にほん語です。"));
+        assert_eq!(
+            warnings,
+            [
+                KanaError::UnknownChar {
+                    character: '語',
+                    position: 9
+                },
+                KanaError::UnknownChar {
+                    character: '。',
+                    position: 18
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aligned() {
+        let input = "ガッコウ、ぎゅうにゅう";
+        let (romaji, alignment) = kana_to_romaji_aligned(input, System::Hepburn);
+        assert_eq!(romaji, kana_to_romaji_hepburn(input));
+
+        let pieces: Vec<_> = alignment
+            .spans()
+            .iter()
+            .map(|span| (&input[span.source.clone()], &romaji[span.output.clone()]))
+            .collect();
+        assert_eq!(
+            pieces,
+            [
+                ("ガ", "ga"),
+                ("ッ", "k"),
+                ("コ", "ko"),
+                ("ウ", "u"),
+                ("、", ""),
+                ("ぎゅ", "gyu"),
+                ("う", "u"),
+                ("にゅ", "nyu"),
+                ("う", "u"),
+            ]
+        );
+
+        // Long vowels stay with the kana they lengthen
+        let (romaji, alignment) = convert_kana_to_romaji_aligned(
+            "トーキョー",
+            Rules {
+                long_vowel: LongVowel::Macron,
+                ..Rules::new(System::Hepburn)
+            },
+        );
+        assert_eq!(romaji, "tōkyō");
+        assert_eq!(alignment.output_range(0..6), 0.."tō".len());
+        assert_eq!(
+            alignment.source_range(3..romaji.len()),
+            6.."トーキョー".len()
+        );
+    }
+
+    #[test]
+    fn test_converter() {
+        let mut converter = KanaToRomajiConverter::new(System::Kunrei);
+        assert_eq!(converter.convert("シャシン"), "syasin");
+
+        // Appends to what is already there
+        let mut out = String::from("> ");
+        converter.convert_into("ちゃわん", &mut out);
+        converter.convert_into("ー", &mut out);
+        assert_eq!(out, "> tyawan-");
+
+        let mut out = String::new();
+        converter.write_to("カッパ", &mut out).unwrap();
+        assert_eq!(out, "kappa");
+
+        let mut bytes = Vec::new();
+        converter.write_io("ミカヅキ", &mut bytes).unwrap();
+        assert_eq!(bytes, b"mikazuki");
+
+        let options = HepburnOptions {
+            long_vowel: LongVowel::Macron,
+            ..Default::default()
+        };
+        let mut converter = KanaToRomajiConverter::hepburn_with(&options);
+        for input in ["トーキョー", "おおさか", "シンブン"] {
+            assert_eq!(
+                converter.convert(input),
+                kana_to_romaji_hepburn_with(input, &options)
+            );
+        }
+    }
+
+    #[test]
+    fn test_converter_long_vowel_into_existing_buffer() {
+        // The long vowel rewrite must only touch what this call wrote
+        let options = HepburnOptions {
+            long_vowel: LongVowel::Macron,
+            ..Default::default()
+        };
+        let mut converter = KanaToRomajiConverter::hepburn_with(&options);
+        let mut out = String::from("o");
+        converter.convert_into("ーオウ", &mut out);
+        assert_eq!(out, "o-ō");
+    }
+
+    #[test]
+    fn test_custom_table() {
+        let mut table = Table::new();
+        table.insert_kana("ヴ", "vu", "vu", "vu");
+        table.insert_kana("ヴァ", "va", "ba", "va");
+        table.insert_kana("ㇰ", "k", "k", "k");
+
+        assert_eq!(
+            kana_to_romaji_with_table("ヴァイオリン", System::Hepburn, &table),
+            "vaiorin"
+        );
+        assert_eq!(
+            kana_to_romaji_with_table("ヴァイオリン", System::Kunrei, &table),
+            "baiorin"
+        );
+        assert_eq!(
+            kana_to_romaji_with_table("ヴ", System::NihonShiki, &table),
+            "vu"
+        );
+        // Small tsu and ン still look at the custom entries
+        assert_eq!(
+            kana_to_romaji_with_table("ッヴァ", System::Hepburn, &table),
+            "vva"
+        );
+        assert_eq!(
+            kana_to_romaji_with_table("アイヌㇰ", System::Hepburn, &table),
+            "ainuk"
+        );
+        // The default table is unaffected
+        assert_eq!(kana_to_romaji_kunrei("ヴァ"), "va");
+    }
+
+    #[test]
+    fn test_v_sounds() {
+        assert_eq!(kana_to_romaji_hepburn("ヴァイオリン"), "vaiorin");
+        assert_eq!(kana_to_romaji_hepburn("ゔぃーなす"), "vi-nasu");
+        assert_eq!(kana_to_romaji_kunrei("ヴ"), "vu");
+        assert_eq!(kana_to_romaji_nihon_shiki("ヴェヴォ"), "vevo");
+        for kana in ["ヴ", "ヴァ", "ヴィ", "ヴェ", "ヴォ"] {
+            let romaji = kana_to_romaji_hepburn(kana);
+            assert_eq!(
+                crate::core::romaji_to_kana::romaji_to_katakana(&romaji),
+                kana
+            );
+        }
+    }
+
+    #[test]
+    fn test_passport() {
+        // Examples from the Ministry of Foreign Affairs' Hepburn table
+        assert_eq!(kana_to_romaji_passport("なんば"), "namba");
+        assert_eq!(kana_to_romaji_passport("ほんま"), "homma");
+        assert_eq!(kana_to_romaji_passport("さんぺい"), "sampei");
+        assert_eq!(kana_to_romaji_passport("はっちょう"), "hatcho");
+        assert_eq!(kana_to_romaji_passport("きっかわ"), "kikkawa");
+        assert_eq!(kana_to_romaji_passport("おおの"), "ono");
+        assert_eq!(kana_to_romaji_passport("おおた"), "ota");
+        assert_eq!(kana_to_romaji_passport("さいとう"), "saito");
+        assert_eq!(kana_to_romaji_passport("こうの"), "kono");
+        assert_eq!(kana_to_romaji_passport("ゆうこ"), "yuko");
+        assert_eq!(kana_to_romaji_passport("きょうこ"), "kyoko");
+        assert_eq!(kana_to_romaji_passport("ちづる"), "chizuru");
+        assert_eq!(kana_to_romaji_passport("しんいち"), "shinichi");
+        assert_eq!(kana_to_romaji_passport("ジュンコ"), "junko");
+
+        // Two long-vowel spellings in a row: only the first is shortened
+        assert_eq!(kana_to_romaji_passport("おおうち"), "ouchi");
+        assert_eq!(kana_to_romaji_passport("ごうう"), "gou");
+        assert_eq!(kana_to_romaji_passport("おおおか"), "ooka");
+        assert_eq!(kana_to_romaji_passport("こうおか"), "kooka");
+
+        // えい and いい keep both vowels
+        assert_eq!(kana_to_romaji_passport("えいこ"), "eiko");
+        assert_eq!(kana_to_romaji_passport("にいがた"), "niigata");
+    }
+
+    #[test]
+    fn test_passport_spellings() {
+        assert_eq!(kana_to_romaji_passport("を"), "o");
+        assert_eq!(kana_to_romaji_passport("ヴ"), "bu");
+        assert_eq!(kana_to_romaji_passport("ヴァイオリン"), "baiorin");
+        assert_eq!(kana_to_romaji_passport("ヴィヴェヴォ"), "bibebo");
+        assert_eq!(kana_to_romaji_passport("ゔぇにす"), "benisu");
+        assert_eq!(kana_to_romaji_passport("ヴュ"), "buyu");
+        assert_eq!(kana_to_romaji_passport("ティナ"), "teina");
+        assert_eq!(kana_to_romaji_hepburn("ティナ"), "tina");
+    }
+
+    #[test]
+    fn test_passport_oh() {
+        let oh = HepburnOptions {
+            long_vowel: LongVowel::Oh,
+            ..HepburnOptions::passport()
+        };
+        assert_eq!(kana_to_romaji_hepburn_with("おおの", &oh), "ohno");
+        assert_eq!(kana_to_romaji_hepburn_with("さいとう", &oh), "saitoh");
+        assert_eq!(kana_to_romaji_hepburn_with("ゆうこ", &oh), "yuko");
+    }
+
+    /// Syllables that may appear anywhere in a round-trippable kana string.
+    fn round_trip_units() -> Vec<String> {
+        let mut units: Vec<String> = "あいうえおかきくけこさしすせそたちつてとなにぬねの\
+            はひふへほまみむめもやゆよらりるれろわをん\
+            がぎぐげござじずぜぞだぢづでどばびぶべぼぱぴぷぺぽ"
+            .chars()
+            .map(String::from)
+            .collect();
+        for c in "きしちにひみりぎじぢびぴ".chars() {
+            for small in "ゃゅょ".chars() {
+                units.push(format!("{}{}", c, small));
+            }
+        }
+        units.push("ー".to_string());
+        units
+    }
+
+    /// Whether っ may precede `unit`, i.e. its romaji starts with a consonant other than n.
+    fn can_follow_sokuon(unit: &str) -> bool {
+        !matches!(
+            unit.chars().next().unwrap(),
+            'あ' | 'い' | 'う' | 'え' | 'お' | 'な' | 'に' | 'ぬ' | 'ね' | 'の' | 'ん' | 'ー'
+        )
+    }
+
+    fn to_katakana(s: &str) -> String {
+        crate::core::kana::hiragana_to_katakana(s)
+    }
+
+    fn assert_round_trip(kana: &str) {
+        let romaji = kana_to_romaji_nihon_shiki(kana);
+        assert_eq!(
+            crate::core::romaji_to_kana::romaji_to_kana(&romaji),
+            kana,
+            "via {}",
+            romaji
+        );
+        let katakana = to_katakana(kana);
+        assert_eq!(
+            crate::core::romaji_to_kana::romaji_to_katakana(&kana_to_romaji_nihon_shiki(&katakana)),
+            katakana,
+            "via {}",
+            romaji
+        );
+    }
+
+    #[test]
+    fn test_round_trip_exhaustive_pairs() {
+        let units = round_trip_units();
+        for a in &units {
+            assert_round_trip(a);
+            for b in &units {
+                assert_round_trip(&format!("{}{}", a, b));
+                if can_follow_sokuon(b) {
+                    assert_round_trip(&format!("{}っ{}", a, b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_random() {
+        // Small xorshift generator so the test is reproducible without extra dependencies.
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = move |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        };
+
+        let units = round_trip_units();
+        for _ in 0..2000 {
+            let mut kana = String::new();
+            for _ in 0..next(12) + 1 {
+                let unit = &units[next(units.len())];
+                if next(5) == 0 && can_follow_sokuon(unit) {
+                    kana.push('っ');
+                }
+                kana.push_str(unit);
+            }
+            assert_round_trip(&kana);
+        }
+    }
+
+    #[test]
+    fn test_decomposed_sound_marks() {
+        // As in macOS file names: "か" + U+3099
+        assert_eq!(
+            kana_to_romaji_hepburn("か\u{3099}っこう"),
+            kana_to_romaji_hepburn("がっこう")
+        );
+        assert_eq!(kana_to_romaji_hepburn("ハ\u{309A}ン"), "pan");
+        assert_eq!(kana_to_romaji_hepburn("ひ゛る"), "biru");
+        assert_eq!(kana_to_romaji_kunrei("ち\u{3099}"), "zi");
+
+        let (romaji, alignment) = kana_to_romaji_aligned("か\u{3099}き", System::Hepburn);
+        assert_eq!(romaji, "gaki");
+        assert_eq!(alignment.source_range(0..2), 0..6);
+        assert_eq!(alignment.source_range(2..4), 6..9);
+
+        let (_, warnings) = kana_to_romaji_with_warnings("ア\u{3099}ワ\u{3099}", System::Hepburn);
+        assert_eq!(
+            warnings,
+            [
+                KanaError::UnknownChar {
+                    character: '\u{3099}',
+                    position: 3
+                },
+                KanaError::UnknownChar {
+                    character: 'ヷ',
+                    position: 6
+                },
+            ]
+        );
+
+        // A mark after a small kana stays separate, like any other unknown character
+        let (romaji, warnings) = kana_to_romaji_with_warnings("っ\u{3099}た", System::Hepburn);
+        assert_eq!(romaji, "ta");
+        assert_eq!(
+            warnings,
+            [KanaError::UnknownChar {
+                character: '\u{3099}',
+                position: 3
+            }]
+        );
+    }
 }
@@ -1,5 +1,45 @@
 pub mod romaji_to_kana;
-pub use romaji_to_kana::romaji_to_kana;
+pub use romaji_to_kana::{
+    RomajiError, RomajiToKanaConverter, romaji_to_kana, romaji_to_kana_aligned,
+    romaji_to_kana_strict, romaji_to_kana_with_table, romaji_to_kana_with_warnings,
+    romaji_to_katakana, romaji_to_katakana_strict, try_romaji_to_kana, try_romaji_to_katakana,
+};
 
 pub mod kana_to_romaji;
-pub use kana_to_romaji::{kana_to_romaji_hepburn, kana_to_romaji_kunrei};
+pub use kana_to_romaji::{
+    Case, HepburnOptions, KanaError, KanaToRomajiConverter, LongVowel, NSeparator, PassThrough,
+    Sokuon, System, kana_to_romaji, kana_to_romaji_aligned, kana_to_romaji_hepburn,
+    kana_to_romaji_hepburn_with, kana_to_romaji_kunrei, kana_to_romaji_nihon_shiki,
+    kana_to_romaji_passport, kana_to_romaji_with_table, kana_to_romaji_with_warnings,
+    try_kana_to_romaji,
+};
+
+pub mod table;
+pub use table::{Table, TableError};
+
+pub mod scheme;
+pub use scheme::{Scheme, SchemeBuilder};
+
+pub mod romaji_input;
+pub use romaji_input::RomajiInput;
+
+pub mod alignment;
+pub use alignment::{Alignment, Span};
+
+pub mod kana;
+pub use kana::{compose_sound_marks, hiragana_to_katakana, katakana_to_hiragana};
+
+pub mod width;
+pub use width::{normalize_width, to_full_width, to_half_width};
+
+pub mod classify;
+pub use classify::{
+    Script, ScriptRuns, is_hiragana, is_japanese_punctuation, is_kana, is_kana_small, is_kanji,
+    is_katakana, script_of, split_by_script,
+};
+
+pub mod segment;
+pub use segment::segment_words;
+
+pub mod gojuon;
+pub use gojuon::{Column, Row};
@@ -0,0 +1,245 @@
+use super::kana::push_composed;
+use super::romaji_to_kana::{Script, Step, next_step, push_kana};
+use super::table::{Table, default_table};
+
+/// Incremental Romaji to Kana conversion for live typing.
+///
+/// ローマ字を一文字ずつ受け取り、確定したかなと未確定のローマ字を管理します。
+///
+/// Keys go in one at a time with [`RomajiInput::push`]. Kana are committed as soon as
+/// no further key can change them; the romaji typed since then stays in
+/// [`RomajiInput::pending`]. [`RomajiInput::finish`] commits what is left, e.g. a trailing
+/// "n" as ん.
+///
+/// The same tables and rules as [`romaji_to_kana`](crate::core::romaji_to_kana::romaji_to_kana)
+/// are used, so typing a string key by key and finishing gives the same result.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::romaji_input::RomajiInput;
+///
+/// let mut input = RomajiInput::new();
+/// assert_eq!(input.push('k'), "");
+/// assert_eq!(input.pending(), "k");
+/// assert_eq!(input.push('y'), "");
+/// assert_eq!(input.pending(), "ky");
+/// assert_eq!(input.push('a'), "きゃ");
+/// assert_eq!(input.pending(), "");
+///
+/// input.push('n');
+/// assert_eq!(input.committed(), "きゃ");
+/// assert_eq!(input.finish(), "ん");
+/// assert_eq!(input.committed(), "きゃん");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RomajiInput<'a> {
+    table: &'a Table,
+    script: Script,
+    committed: String,
+    pending: String,
+}
+
+impl RomajiInput<'static> {
+    /// Input producing Hiragana.
+    pub fn new() -> Self {
+        RomajiInput::with_table(default_table())
+    }
+
+    /// Input producing Katakana.
+    pub fn katakana() -> Self {
+        RomajiInput {
+            script: Script::Katakana,
+            ..RomajiInput::new()
+        }
+    }
+}
+
+impl Default for RomajiInput<'static> {
+    fn default() -> Self {
+        RomajiInput::new()
+    }
+}
+
+impl<'a> RomajiInput<'a> {
+    /// Input producing Hiragana using a custom [`Table`].
+    pub fn with_table(table: &'a Table) -> Self {
+        RomajiInput {
+            table,
+            script: Script::Hiragana,
+            committed: String::new(),
+            pending: String::new(),
+        }
+    }
+
+    /// Type one key. Returns the kana committed by this key, if any.
+    pub fn push(&mut self, key: char) -> &str {
+        let start = self.committed.len();
+        self.pending.push(key);
+        self.resolve(false);
+        &self.committed[start..]
+    }
+
+    /// Delete the last pending romaji key, or else the last committed char.
+    ///
+    /// Returns the deleted char, or `None` when everything is empty.
+    pub fn backspace(&mut self) -> Option<char> {
+        self.pending.pop().or_else(|| self.committed.pop())
+    }
+
+    /// Commit the pending romaji as if the input ended here. Returns the newly committed kana.
+    pub fn finish(&mut self) -> &str {
+        let start = self.committed.len();
+        self.resolve(true);
+        &self.committed[start..]
+    }
+
+    /// Kana committed so far.
+    pub fn committed(&self) -> &str {
+        &self.committed
+    }
+
+    /// Romaji typed after the last committed kana.
+    pub fn pending(&self) -> &str {
+        &self.pending
+    }
+
+    /// Take the committed kana out, leaving the pending romaji in place.
+    pub fn take_committed(&mut self) -> String {
+        std::mem::take(&mut self.committed)
+    }
+
+    /// Discard both committed kana and pending romaji.
+    pub fn clear(&mut self) {
+        self.committed.clear();
+        self.pending.clear();
+    }
+
+    fn resolve(&mut self, complete: bool) {
+        let mut consumed = 0;
+        while consumed < self.pending.len() {
+            let remaining = &self.pending[consumed..];
+            consumed += match next_step(remaining, self.table, complete) {
+                Step::Kana(kana, len) => {
+                    push_kana(&mut self.committed, kana, self.script);
+                    len
+                }
+                Step::AmbiguousN => {
+                    push_kana(&mut self.committed, "ん", self.script);
+                    1
+                }
+                Step::Copy(c) => {
+                    push_composed(&mut self.committed, c);
+                    c.len_utf8()
+                }
+                Step::NeedMore => break,
+            };
+        }
+        self.pending.drain(..consumed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::romaji_to_kana::{romaji_to_kana, romaji_to_katakana};
+
+    fn type_all(input: &mut RomajiInput, keys: &str) -> Vec<String> {
+        keys.chars().map(|c| input.push(c).to_string()).collect()
+    }
+
+    #[test]
+    fn test_keystrokes() {
+        let mut input = RomajiInput::new();
+        assert_eq!(type_all(&mut input, "kya"), ["", "", "きゃ"]);
+        assert_eq!(type_all(&mut input, "tto"), ["", "っ", "と"]);
+        assert_eq!(
+            type_all(&mut input, "matcha"),
+            ["", "ま", "", "", "っ", "ちゃ"]
+        );
+        assert_eq!(input.committed(), "きゃっとまっちゃ");
+        assert_eq!(input.pending(), "");
+    }
+
+    #[test]
+    fn test_n() {
+        let mut input = RomajiInput::new();
+        // "n" waits: it may become な, ん, or にゃ
+        assert_eq!(input.push('n'), "");
+        assert_eq!(input.push('k'), "ん");
+        assert_eq!(input.pending(), "k");
+        input.clear();
+
+        // "nn" waits for the next key, like romaji_to_kana
+        assert_eq!(type_all(&mut input, "nn"), ["", ""]);
+        assert_eq!(input.push('i'), "んに");
+        assert_eq!(input.pending(), "");
+        input.clear();
+
+        assert_eq!(type_all(&mut input, "nnk"), ["", "", "ん"]);
+        input.clear();
+
+        // A trailing n becomes ん only on finish
+        assert_eq!(type_all(&mut input, "hon"), ["", "ほ", ""]);
+        assert_eq!(input.pending(), "n");
+        assert_eq!(input.finish(), "ん");
+        assert_eq!(input.committed(), "ほん");
+
+        input.clear();
+        assert_eq!(type_all(&mut input, "n'"), ["", "ん"]);
+    }
+
+    #[test]
+    fn test_backspace() {
+        let mut input = RomajiInput::new();
+        type_all(&mut input, "kaky");
+        assert_eq!(input.backspace(), Some('y'));
+        assert_eq!(input.backspace(), Some('k'));
+        assert_eq!(input.backspace(), Some('か'));
+        assert_eq!(input.backspace(), None);
+
+        type_all(&mut input, "sy");
+        input.backspace();
+        assert_eq!(input.push('h'), "");
+        assert_eq!(input.push('i'), "し");
+    }
+
+    #[test]
+    fn test_matches_batch_conversion() {
+        for text in [
+            "konnichiha",
+            "kin'yuu",
+            "gakkou",
+            "matcha",
+            "konnnichiha",
+            "honn",
+            "shinbun",
+            "xtsu",
+            "ok!",
+            "tcha",
+            "tc",
+            "ka\u{3099}",
+            "ha゜n",
+        ] {
+            let mut input = RomajiInput::new();
+            type_all(&mut input, text);
+            input.finish();
+            assert_eq!(input.committed(), romaji_to_kana(text), "{}", text);
+            assert_eq!(input.pending(), "");
+
+            let mut input = RomajiInput::katakana();
+            type_all(&mut input, text);
+            input.finish();
+            assert_eq!(input.committed(), romaji_to_katakana(text), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_take_committed() {
+        let mut input = RomajiInput::new();
+        type_all(&mut input, "sushik");
+        assert_eq!(input.take_committed(), "すし");
+        assert_eq!(input.committed(), "");
+        assert_eq!(input.pending(), "k");
+    }
+}
@@ -1,3 +1,9 @@
+use std::{fmt, io};
+
+use super::alignment::{Alignment, Span};
+use super::kana::{push_composed, to_katakana};
+use super::table::{Table, default_table};
+
 /// Convert Romaji to Hiragana.
 ///
 /// ローマ字をひらがなに変換します。
@@ -7,57 +13,497 @@
 /// ```
 /// use buchikun::core::romaji_to_kana::romaji_to_kana;
 /// assert_eq!(romaji_to_kana("konnichiha"), "こんにちは");
+/// assert_eq!(romaji_to_kana("kin'yuu"), "きんゆう");
 /// ```
+///
+/// Romaji is matched ignoring case, so "Tokyo" and "TOKYO" give the same kana.
+///
+/// ん is written "n" before a consonant or at the end, "n'" or "nn" anywhere.
+/// "nn" followed by a vowel or "y" is read as ん plus a な-row syllable, so "konnichiha"
+/// is こんにちは; use [`romaji_to_kana_strict`] to reject that spelling instead.
 pub fn romaji_to_kana(input: &str) -> String {
-    let mut result = String::new();
+    match convert_romaji_to_kana(input, default_table(), Script::Hiragana, false) {
+        Ok(result) => result,
+        Err(_) => unreachable!("lenient conversion does not fail"),
+    }
+}
+
+/// Convert Romaji to Hiragana, rejecting ambiguous spellings of ん.
+///
+/// ローマ字をひらがなに変換します。「ん」の綴りが曖昧な場合はエラーを返します。
+///
+/// "nn" followed by a vowel or "y" could be either ん + な-row or ん + あ-row
+/// (こんにちは / こんいちは), so it is reported as [`RomajiError::AmbiguousN`].
+/// Write "n'" or "nnn" to make the intent explicit.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::romaji_to_kana::{romaji_to_kana_strict, RomajiError};
+/// assert_eq!(romaji_to_kana_strict("kon'nichiha"), Ok("こんにちは".to_string()));
+/// assert_eq!(romaji_to_kana_strict("konnnichiha"), Ok("こんにちは".to_string()));
+/// assert_eq!(romaji_to_kana_strict("konnichiha"), Err(RomajiError::AmbiguousN { position: 2 }));
+/// ```
+pub fn romaji_to_kana_strict(input: &str) -> Result<String, RomajiError> {
+    convert_romaji_to_kana(input, default_table(), Script::Hiragana, true)
+}
+
+/// Convert Romaji to Katakana.
+///
+/// ローマ字をカタカナに変換します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::romaji_to_kana::romaji_to_katakana;
+/// assert_eq!(romaji_to_katakana("konpyu-ta-"), "コンピューター");
+/// ```
+pub fn romaji_to_katakana(input: &str) -> String {
+    match convert_romaji_to_kana(input, default_table(), Script::Katakana, false) {
+        Ok(result) => result,
+        Err(_) => unreachable!("lenient conversion does not fail"),
+    }
+}
+
+/// Convert Romaji to Katakana, rejecting ambiguous spellings of ン.
+///
+/// ローマ字をカタカナに変換します。「ン」の綴りが曖昧な場合はエラーを返します。
+/// See [`romaji_to_kana_strict`].
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::romaji_to_kana::{romaji_to_katakana_strict, RomajiError};
+/// assert_eq!(romaji_to_katakana_strict("an'na"), Ok("アンナ".to_string()));
+/// assert_eq!(romaji_to_katakana_strict("anna"), Err(RomajiError::AmbiguousN { position: 1 }));
+/// ```
+pub fn romaji_to_katakana_strict(input: &str) -> Result<String, RomajiError> {
+    convert_romaji_to_kana(input, default_table(), Script::Katakana, true)
+}
 
-    // We'll use a loop and advance manually
-    // However, since we need lookahead for longest match, working with a string slice or char vec is easier.
-    // Let's use the remaining string slice.
+/// Convert Romaji to Hiragana, rejecting characters that do not convert.
+///
+/// ローマ字をひらがなに変換します。変換できない文字がある場合はエラーを返します。
+///
+/// [`romaji_to_kana`] copies such characters through as they are; here the first one is
+/// reported as [`RomajiError::UnknownChar`]. Whitespace is copied and never reported.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::romaji_to_kana::{try_romaji_to_kana, RomajiError};
+/// assert_eq!(try_romaji_to_kana("sushi tabetai"), Ok("すし たべたい".to_string()));
+/// assert_eq!(
+///     try_romaji_to_kana("sushi2"),
+///     Err(RomajiError::UnknownChar { character: '2', position: 5 })
+/// );
+/// ```
+pub fn try_romaji_to_kana(input: &str) -> Result<String, RomajiError> {
+    first_warning(convert_with_warnings(input, Script::Hiragana))
+}
 
+/// Convert Romaji to Katakana, rejecting characters that do not convert.
+///
+/// ローマ字をカタカナに変換します。変換できない文字がある場合はエラーを返します。
+/// See [`try_romaji_to_kana`].
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::romaji_to_kana::{try_romaji_to_katakana, RomajiError};
+/// assert_eq!(try_romaji_to_katakana("ko-hi-"), Ok("コーヒー".to_string()));
+/// assert_eq!(
+///     try_romaji_to_katakana("kqo"),
+///     Err(RomajiError::UnknownChar { character: 'k', position: 0 })
+/// );
+/// ```
+pub fn try_romaji_to_katakana(input: &str) -> Result<String, RomajiError> {
+    first_warning(convert_with_warnings(input, Script::Katakana))
+}
+
+/// Convert Romaji to Hiragana like [`romaji_to_kana`], also reporting what was copied through.
+///
+/// ローマ字をひらがなに変換し、変換できずにそのまま残した文字を警告として返します。
+///
+/// The warnings are the errors [`try_romaji_to_kana`] would report, in input order.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::romaji_to_kana::{romaji_to_kana_with_warnings, RomajiError};
+///
+/// let (kana, warnings) = romaji_to_kana_with_warnings("iphone15");
+/// assert_eq!(kana, "いpほね15");
+/// assert_eq!(
+///     warnings,
+///     [
+///         RomajiError::UnknownChar { character: 'p', position: 1 },
+///         RomajiError::UnknownChar { character: '1', position: 6 },
+///         RomajiError::UnknownChar { character: '5', position: 7 },
+///     ]
+/// );
+/// ```
+pub fn romaji_to_kana_with_warnings(input: &str) -> (String, Vec<RomajiError>) {
+    convert_with_warnings(input, Script::Hiragana)
+}
+
+/// Convert Romaji to Hiragana using a custom [`Table`].
+///
+/// 独自の変換表を使ってローマ字をひらがなに変換します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::romaji_to_kana::romaji_to_kana_with_table;
+/// use buchikun::core::table::Table;
+///
+/// let mut table = Table::new();
+/// table.insert_romaji("wi", "ゐ");
+/// assert_eq!(romaji_to_kana_with_table("wisuki-", &table), "ゐすきー");
+/// ```
+pub fn romaji_to_kana_with_table(input: &str, table: &Table) -> String {
+    match convert_romaji_to_kana(input, table, Script::Hiragana, false) {
+        Ok(result) => result,
+        Err(_) => unreachable!("lenient conversion does not fail"),
+    }
+}
+
+/// Convert Romaji to Hiragana, also returning which romaji produced which kana.
+///
+/// ローマ字をひらがなに変換し、入力と出力の対応関係も返します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::romaji_to_kana::romaji_to_kana_aligned;
+///
+/// let (kana, alignment) = romaji_to_kana_aligned("kitte");
+/// assert_eq!(kana, "きって");
+/// let spans: Vec<_> = alignment
+///     .spans()
+///     .iter()
+///     .map(|span| (&"kitte"[span.source.clone()], &kana[span.output.clone()]))
+///     .collect();
+/// assert_eq!(spans, [("ki", "き"), ("t", "っ"), ("te", "て")]);
+/// ```
+pub fn romaji_to_kana_aligned(input: &str) -> (String, Alignment) {
+    let mut result = String::with_capacity(input.len() * 3);
+    let mut spans = Vec::new();
+    match write_romaji_to_kana(
+        input,
+        default_table(),
+        Script::Hiragana,
+        false,
+        &mut result,
+        Some(&mut spans),
+        None,
+    ) {
+        Ok(()) => (result, Alignment::new(spans)),
+        Err(_) => unreachable!("lenient conversion does not fail"),
+    }
+}
+
+/// Reusable Romaji to Kana converter that writes into existing buffers.
+///
+/// The converter keeps its working buffer between calls, so writing to
+/// [`fmt::Write`] or [`io::Write`] sinks does not allocate per call.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::romaji_to_kana::RomajiToKanaConverter;
+///
+/// let mut converter = RomajiToKanaConverter::katakana();
+/// let mut out = String::new();
+/// for word in ["ko-hi-", "kukki-"] {
+///     converter.convert_into(word, &mut out);
+///     out.push(' ');
+/// }
+/// assert_eq!(out, "コーヒー クッキー ");
+/// ```
+#[derive(Debug, Clone)]
+pub struct RomajiToKanaConverter<'a> {
+    table: &'a Table,
+    script: Script,
+    buffer: String,
+}
+
+impl RomajiToKanaConverter<'static> {
+    /// Converter to Hiragana, same as [`romaji_to_kana`].
+    pub fn new() -> Self {
+        RomajiToKanaConverter::with_table(default_table())
+    }
+
+    /// Converter to Katakana, same as [`romaji_to_katakana`].
+    pub fn katakana() -> Self {
+        RomajiToKanaConverter {
+            script: Script::Katakana,
+            ..RomajiToKanaConverter::new()
+        }
+    }
+}
+
+impl Default for RomajiToKanaConverter<'static> {
+    fn default() -> Self {
+        RomajiToKanaConverter::new()
+    }
+}
+
+impl<'a> RomajiToKanaConverter<'a> {
+    /// Converter to Hiragana using a custom [`Table`], same as [`romaji_to_kana_with_table`].
+    pub fn with_table(table: &'a Table) -> Self {
+        RomajiToKanaConverter {
+            table,
+            script: Script::Hiragana,
+            buffer: String::new(),
+        }
+    }
+
+    /// Convert `input` into a new `String`.
+    pub fn convert(&mut self, input: &str) -> String {
+        let mut result = String::new();
+        self.convert_into(input, &mut result);
+        result
+    }
+
+    /// Append the kana for `input` to `out`.
+    pub fn convert_into(&mut self, input: &str, out: &mut String) {
+        match write_romaji_to_kana(input, self.table, self.script, false, out, None, None) {
+            Ok(()) => {}
+            Err(_) => unreachable!("lenient conversion does not fail"),
+        }
+    }
+
+    /// Write the kana for `input` to a [`fmt::Write`] sink.
+    pub fn write_to<W: fmt::Write>(&mut self, input: &str, out: &mut W) -> fmt::Result {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        self.convert_into(input, &mut buffer);
+        let result = out.write_str(&buffer);
+        self.buffer = buffer;
+        result
+    }
+
+    /// Write the kana for `input` as UTF-8 to an [`io::Write`] sink.
+    pub fn write_io<W: io::Write>(&mut self, input: &str, out: &mut W) -> io::Result<()> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        self.convert_into(input, &mut buffer);
+        let result = out.write_all(buffer.as_bytes());
+        self.buffer = buffer;
+        result
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RomajiError {
+    /// "nn" followed by a vowel or "y" at the given byte offset.
+    AmbiguousN { position: usize },
+    /// A character that is not part of any romaji spelling, at the given byte offset.
+    UnknownChar { character: char, position: usize },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Script {
+    Hiragana,
+    Katakana,
+}
+
+fn convert_with_warnings(input: &str, script: Script) -> (String, Vec<RomajiError>) {
+    let mut result = String::with_capacity(input.len() * 3);
+    let mut warnings = Vec::new();
+    match write_romaji_to_kana(
+        input,
+        default_table(),
+        script,
+        false,
+        &mut result,
+        None,
+        Some(&mut warnings),
+    ) {
+        Ok(()) => (result, warnings),
+        Err(_) => unreachable!("lenient conversion does not fail"),
+    }
+}
+
+fn first_warning((result, warnings): (String, Vec<RomajiError>)) -> Result<String, RomajiError> {
+    match warnings.into_iter().next() {
+        Some(error) => Err(error),
+        None => Ok(result),
+    }
+}
+
+fn convert_romaji_to_kana(
+    input: &str,
+    table: &Table,
+    script: Script,
+    strict: bool,
+) -> Result<String, RomajiError> {
+    let mut result = String::with_capacity(input.len() * 3);
+    write_romaji_to_kana(input, table, script, strict, &mut result, None, None)?;
+    Ok(result)
+}
+
+/// Append the kana for `input` to `result`.
+///
+/// With `spans`, also record which bytes of `input` produced which bytes of `result`.
+/// With `warnings`, also record the characters copied through unconverted.
+/// Sound marks only compose with kana written by this call, not with what `result` already held.
+fn write_romaji_to_kana(
+    input: &str,
+    table: &Table,
+    script: Script,
+    strict: bool,
+    result: &mut String,
+    mut spans: Option<&mut Vec<Span>>,
+    mut warnings: Option<&mut Vec<RomajiError>>,
+) -> Result<(), RomajiError> {
+    let start = result.len();
     let mut current_idx = 0;
     while current_idx < input.len() {
         let remaining = &input[current_idx..];
+        let output_start = result.len();
+        let mut composed = false;
+        let consumed = match next_step(remaining, table, true) {
+            Step::Kana(kana, len) => {
+                push_kana(result, kana, script);
+                len
+            }
+            Step::AmbiguousN if strict => {
+                return Err(RomajiError::AmbiguousN {
+                    position: current_idx,
+                });
+            }
+            Step::AmbiguousN => {
+                push_kana(result, "ん", script);
+                1
+            }
+            Step::Copy(c) => {
+                // A sound mark after kana ("か" + U+3099) is composed, not copied.
+                if output_start > start {
+                    composed = push_composed(result, c);
+                } else {
+                    result.push(c);
+                }
+                if !composed
+                    && !c.is_whitespace()
+                    && let Some(warnings) = warnings.as_deref_mut()
+                {
+                    warnings.push(RomajiError::UnknownChar {
+                        character: c,
+                        position: current_idx,
+                    });
+                }
+                c.len_utf8()
+            }
+            Step::NeedMore => unreachable!("complete input always decides"),
+        };
+        if let Some(spans) = spans.as_deref_mut() {
+            match spans.last_mut() {
+                // The mark now belongs to the kana it was composed with.
+                Some(last) if composed => last.source.end = current_idx + consumed,
+                _ => spans.push(Span {
+                    source: current_idx..current_idx + consumed,
+                    output: output_start..result.len(),
+                }),
+            }
+        }
+        current_idx += consumed;
+    }
+
+    Ok(())
+}
 
-        // Try to find a match in the map
-        // The map should be ordered by length descending effectively
-        // Since we don't want to iterate a huge list every time, we can try to match based on known prefixes.
-        // But a simple iteration over a static sorted list is fine for reasonable input length.
+/// What to do with the start of the remaining romaji.
+pub(crate) enum Step<'t> {
+    /// Emit the Hiragana and consume the given number of bytes.
+    Kana(&'t str, usize),
+    /// "nn" before a vowel or 'y': emit ん and consume only the first 'n'.
+    AmbiguousN,
+    /// No mapping: copy the char as is.
+    Copy(char),
+    /// `remaining` is not `complete` and the next chars could change the result.
+    NeedMore,
+}
 
-        if let Some((len, kana)) = find_match(remaining) {
-            result.push_str(kana);
-            current_idx += len;
-            continue;
+/// Decide the next conversion step for `remaining`.
+///
+/// `complete` tells whether `remaining` runs to the end of the input; when it does not
+/// (live typing), steps that depend on chars not typed yet return [`Step::NeedMore`].
+pub(crate) fn next_step<'t>(remaining: &str, table: &'t Table, complete: bool) -> Step<'t> {
+    // A longer entry may still match, e.g. "k" -> "ky" -> "kya"
+    if !complete && table.has_longer_romaji(remaining) {
+        return Step::NeedMore;
+    }
+
+    // "nn" is ん. Before a vowel or 'y' only the first 'n' is consumed,
+    // so the second one starts the next syllable ("konnichiha" -> こんにちは).
+    if starts_with_ignore_case(remaining, "nn") {
+        let after = &remaining[2..];
+        if after.is_empty() && !complete {
+            return Step::NeedMore;
+        }
+        if after
+            .bytes()
+            .next()
+            .is_some_and(|b| b"aiueoy".contains(&b.to_ascii_lowercase()))
+        {
+            return Step::AmbiguousN;
         }
+        return Step::Kana("ん", 2);
+    }
 
-        // Handle small tsu (double consonant)
-        // If current char is a consonant and matches next char
-        // Note: We need to be careful not to consume it if it's part of a valid mapping that we missed (unlikely if map is complete)
-        // Rule: if s[0] == s[1] and is consonant -> insert small tsu, consume 1 char (the first consonant)
-        // The second consonant will start the next match.
-        // e.g. "tt" -> "っ" + "t" -> next loop "ta" -> "た" => "った"
-        let first_char = remaining.chars().next().unwrap();
-        let first_char_len = first_char.len_utf8();
-
-        if remaining.len() > first_char_len {
-            let next_char = remaining[first_char_len..].chars().next().unwrap();
-            if first_char == next_char && is_consonant(first_char) {
-                result.push('っ');
-                current_idx += first_char_len;
-                continue;
-            }
+    // Try to find a match in the map
+    // The table keeps its romaji in a trie, so this finds the longest match
+    // without iterating the whole list every time.
+    if let Some((len, kana)) = table.match_romaji(remaining) {
+        return Step::Kana(kana, len);
+    }
+
+    // Handle small tsu (double consonant)
+    // If current char is a consonant and matches next char
+    // Note: We need to be careful not to consume it if it's part of a valid mapping that we missed (unlikely if map is complete)
+    // Rule: if s[0] == s[1] and is consonant -> insert small tsu, consume 1 char (the first consonant)
+    // The second consonant will start the next match.
+    // e.g. "tt" -> "っ" + "t" -> next loop "ta" -> "た" => "った"
+    let first_char = remaining.chars().next().unwrap();
+    let first_char_len = first_char.len_utf8();
+    let rest = &remaining[first_char_len..];
+    // Romaji is matched ignoring case ("Tokyo", "YAMADA")
+    let first = first_char.to_ascii_lowercase();
+
+    if let Some(next_char) = rest.chars().next() {
+        // "tch" is how Hepburn writes っち (e.g. "matcha")
+        if first == 't' && rest.eq_ignore_ascii_case("c") && !complete {
+            return Step::NeedMore;
         }
+        if (first == next_char.to_ascii_lowercase() && is_consonant(first))
+            || (first == 't' && starts_with_ignore_case(rest, "ch"))
+        {
+            return Step::Kana("っ", first_char_len);
+        }
+    }
+
+    // 'n' handling handling
+    // 'n' alone should be 'ん' if it didn't match 'na', 'ni' etc (which are in the map).
+    // If 'n' is mapped in the map as 'ん', it works (since longer keys like 'na' are checked first).
+    // "nn" and "n'" are handled above and in the map respectively.
 
-        // 'n' handling handling
-        // 'n' alone should be 'ん' if it didn't match 'na', 'ni' etc (which are in the map).
-        // If 'n' is mapped in the map as 'ん', it works (since longer keys like 'na' are checked first).
+    // Default: copy char
+    Step::Copy(first_char)
+}
 
-        // Default: copy char
-        result.push(first_char);
-        current_idx += first_char_len;
+/// Append Hiragana from the mapping table, transcribed into the target script.
+pub(crate) fn push_kana(result: &mut String, kana: &str, script: Script) {
+    match script {
+        Script::Hiragana => result.push_str(kana),
+        Script::Katakana => result.extend(kana.chars().map(to_katakana)),
     }
+}
 
-    result
+fn starts_with_ignore_case(s: &str, prefix: &str) -> bool {
+    s.get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
 }
 
 fn is_consonant(c: char) -> bool {
@@ -69,126 +515,6 @@ fn is_consonant(c: char) -> bool {
     }
 }
 
-fn find_match(s: &str) -> Option<(usize, &str)> {
-    // Mappings sorted by length descending
-    // This is a subset of Hepburn, expand as needed
-    const MAPPINGS: &[(&str, &str)] = &[
-        ("kya", "きゃ"),
-        ("kyu", "きゅ"),
-        ("kyo", "きょ"),
-        ("sha", "しゃ"),
-        ("shu", "しゅ"),
-        ("sho", "しょ"),
-        ("cha", "ちゃ"),
-        ("chu", "ちゅ"),
-        ("cho", "ちょ"),
-        ("nya", "にゃ"),
-        ("nyu", "にゅ"),
-        ("nyo", "にょ"),
-        ("hya", "ひゃ"),
-        ("hyu", "ひゅ"),
-        ("hyo", "ひょ"),
-        ("mya", "みゃ"),
-        ("myu", "みゅ"),
-        ("myo", "みょ"),
-        ("rya", "りゃ"),
-        ("ryu", "りゅ"),
-        ("ryo", "りょ"),
-        ("gya", "ぎゃ"),
-        ("gyu", "ぎゅ"),
-        ("gyo", "ぎょ"),
-        ("ja", "じゃ"),
-        ("ju", "じゅ"),
-        ("jo", "じょ"),
-        ("bya", "びゃ"),
-        ("byu", "びゅ"),
-        ("byo", "びょ"),
-        ("pya", "ぴゃ"),
-        ("pyu", "ぴゅ"),
-        ("pyo", "ぴょ"),
-        ("shi", "し"),
-        ("chi", "ち"),
-        ("tsu", "つ"),
-        ("ka", "か"),
-        ("ki", "き"),
-        ("ku", "く"),
-        ("ke", "け"),
-        ("ko", "こ"),
-        ("sa", "さ"),
-        ("su", "す"),
-        ("se", "せ"),
-        ("so", "そ"),
-        ("ta", "た"),
-        ("te", "て"),
-        ("to", "と"),
-        ("na", "な"),
-        ("ni", "に"),
-        ("nu", "ぬ"),
-        ("ne", "ね"),
-        ("no", "の"),
-        ("ha", "は"),
-        ("hi", "ひ"),
-        ("hu", "ふ"),
-        ("fu", "ふ"),
-        ("he", "へ"),
-        ("ho", "ほ"),
-        ("ma", "ま"),
-        ("mi", "み"),
-        ("mu", "む"),
-        ("me", "め"),
-        ("mo", "も"),
-        ("ya", "や"),
-        ("yu", "ゆ"),
-        ("yo", "よ"),
-        ("ra", "ら"),
-        ("ri", "り"),
-        ("ru", "る"),
-        ("re", "れ"),
-        ("ro", "ろ"),
-        ("wa", "わ"),
-        ("wo", "を"),
-        ("ga", "が"),
-        ("gi", "ぎ"),
-        ("gu", "ぐ"),
-        ("ge", "げ"),
-        ("go", "ご"),
-        ("za", "ざ"),
-        ("ji", "じ"),
-        ("zu", "ず"),
-        ("ze", "ぜ"),
-        ("zo", "ぞ"),
-        ("da", "だ"),
-        ("ji", "ぢ"),
-        ("zu", "づ"),
-        ("de", "で"),
-        ("do", "ど"),
-        ("ba", "ば"),
-        ("bi", "び"),
-        ("bu", "ぶ"),
-        ("be", "べ"),
-        ("bo", "ぼ"),
-        ("pa", "ぱ"),
-        ("pi", "ぴ"),
-        ("pu", "ぷ"),
-        ("pe", "ぺ"),
-        ("po", "ぽ"),
-        ("a", "あ"),
-        ("i", "い"),
-        ("u", "う"),
-        ("e", "え"),
-        ("o", "お"),
-        ("n", "ん"),
-        ("-", "ー"),
-    ];
-
-    for (romaji, kana) in MAPPINGS {
-        if s.starts_with(romaji) {
-            return Some((romaji.len(), kana));
-        }
-    }
-    None
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -216,4 +542,305 @@ mod tests {
     fn test_mixed() {
         assert_eq!(romaji_to_kana("romaji"), "ろまじ"); // ro ma ji
     }
+
+    #[test]
+    fn test_katakana() {
+        assert_eq!(romaji_to_katakana("konpyu-ta-"), "コンピューター");
+        assert_eq!(romaji_to_katakana("katakana"), "カタカナ");
+        assert_eq!(romaji_to_katakana("shatsu"), "シャツ");
+        assert_eq!(romaji_to_katakana("kukki-"), "クッキー");
+    }
+
+    #[test]
+    fn test_katakana_pass_through() {
+        // Characters without a mapping are copied as is, in either script.
+        assert_eq!(romaji_to_katakana("ok!"), "オk!");
+        assert_eq!(romaji_to_katakana("ka ka"), "カ カ");
+    }
+
+    #[test]
+    fn test_ime_spellings() {
+        // Kunrei / Nihon-shiki style
+        assert_eq!(romaji_to_kana("sinbun"), "しんぶん");
+        assert_eq!(romaji_to_kana("tikatetu"), "ちかてつ");
+        assert_eq!(romaji_to_kana("zisin"), "じしん");
+        assert_eq!(romaji_to_kana("hanadi"), "はなぢ");
+        assert_eq!(romaji_to_kana("tuduku"), "つづく");
+        assert_eq!(romaji_to_kana("tya"), "ちゃ");
+        assert_eq!(romaji_to_kana("syasin"), "しゃしん");
+        assert_eq!(romaji_to_kana("jyuusu"), "じゅうす");
+        assert_eq!(romaji_to_kana("zyanru"), "じゃんる");
+
+        // Foreign sounds
+        assert_eq!(romaji_to_kana("dhisuku"), "でぃすく");
+        assert_eq!(romaji_to_kana("fairu"), "ふぁいる");
+        assert_eq!(romaji_to_kana("fi-ba-"), "ふぃーばー");
+        assert_eq!(romaji_to_kana("wisuki-"), "うぃすきー");
+        assert_eq!(romaji_to_kana("we-bu"), "うぇーぶ");
+        assert_eq!(romaji_to_kana("vaiorin"), "ゔぁいおりん");
+        assert_eq!(romaji_to_kana("vu"), "ゔ");
+        assert_eq!(romaji_to_kana("twu"), "とぅ");
+        assert_eq!(romaji_to_katakana("thi-"), "ティー");
+    }
+
+    #[test]
+    fn test_ime_small_kana() {
+        assert_eq!(romaji_to_kana("xa"), "ぁ");
+        assert_eq!(romaji_to_kana("la"), "ぁ");
+        assert_eq!(romaji_to_kana("xyu"), "ゅ");
+        assert_eq!(romaji_to_kana("xtu"), "っ");
+        assert_eq!(romaji_to_kana("ltu"), "っ");
+        assert_eq!(romaji_to_kana("xtsu"), "っ");
+        assert_eq!(romaji_to_kana("xwa"), "ゎ");
+        assert_eq!(romaji_to_kana("xka"), "ゕ");
+        assert_eq!(romaji_to_kana("xn"), "ん");
+        assert_eq!(romaji_to_kana("n'"), "ん");
+    }
+
+    #[test]
+    fn test_tch() {
+        assert_eq!(romaji_to_kana("matcha"), "まっちゃ");
+        assert_eq!(romaji_to_kana("kotchi"), "こっち");
+    }
+
+    #[test]
+    fn test_n() {
+        // n before a consonant or at the end
+        assert_eq!(romaji_to_kana("kanji"), "かんじ");
+        assert_eq!(romaji_to_kana("hon"), "ほん");
+        assert_eq!(romaji_to_kana("kinyuu"), "きにゅう");
+
+        // n' and nn
+        assert_eq!(romaji_to_kana("kin'yuu"), "きんゆう");
+        assert_eq!(romaji_to_kana("kin'en"), "きんえん");
+        assert_eq!(romaji_to_kana("hon'ya"), "ほんや");
+        assert_eq!(romaji_to_kana("konnpyu-ta-"), "こんぴゅーたー");
+        assert_eq!(romaji_to_kana("honn"), "ほん");
+
+        // nn before a vowel reads as ん + な-row, nnn makes ん + な-row explicit
+        assert_eq!(romaji_to_kana("anna"), "あんな");
+        assert_eq!(romaji_to_kana("annna"), "あんな");
+        assert_eq!(romaji_to_kana("konnnichiha"), "こんにちは");
+        assert_eq!(romaji_to_kana("konnyaku"), "こんにゃく");
+    }
+
+    #[test]
+    fn test_n_strict() {
+        assert_eq!(romaji_to_kana_strict("kanji"), Ok("かんじ".to_string()));
+        assert_eq!(romaji_to_kana_strict("kin'yuu"), Ok("きんゆう".to_string()));
+        assert_eq!(romaji_to_kana_strict("honn"), Ok("ほん".to_string()));
+        assert_eq!(romaji_to_kana_strict("annna"), Ok("あんな".to_string()));
+
+        assert_eq!(
+            romaji_to_kana_strict("konnichiha"),
+            Err(RomajiError::AmbiguousN { position: 2 })
+        );
+        assert_eq!(
+            romaji_to_kana_strict("kinnyuu"),
+            Err(RomajiError::AmbiguousN { position: 2 })
+        );
+        assert_eq!(
+            romaji_to_katakana_strict("konnyaku"),
+            Err(RomajiError::AmbiguousN { position: 2 })
+        );
+    }
+
+    #[test]
+    fn test_custom_table() {
+        let mut table = Table::new();
+        table.insert_romaji("wi", "ゐ");
+        table.insert_romaji("we", "ゑ");
+        assert_eq!(romaji_to_kana_with_table("wiwe", &table), "ゐゑ");
+        // Small tsu and n handling still apply
+        assert_eq!(romaji_to_kana_with_table("wwi", &table), "っゐ");
+        assert_eq!(
+            romaji_to_kana_with_table("konnichiha", &table),
+            "こんにちは"
+        );
+        // The default table is unaffected
+        assert_eq!(romaji_to_kana("wi"), "うぃ");
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        assert_eq!(romaji_to_kana("Tokyo"), "ときょ");
+        assert_eq!(romaji_to_kana("YAMADA TARO"), "やまだ たろ");
+        assert_eq!(romaji_to_kana("GAKKOU"), "がっこう");
+        assert_eq!(romaji_to_kana("MaTcHa"), "まっちゃ");
+        assert_eq!(romaji_to_kana("KONNNICHIHA"), "こんにちは");
+        assert_eq!(romaji_to_kana("KIN'YUU"), "きんゆう");
+        assert_eq!(romaji_to_katakana("KONPYU-TA-"), "コンピューター");
+        assert_eq!(
+            romaji_to_kana_strict("KONNICHIHA"),
+            Err(RomajiError::AmbiguousN { position: 2 })
+        );
+        // Unknown characters keep their case
+        assert_eq!(romaji_to_kana("KQ"), "KQ");
+    }
+
+    #[test]
+    fn test_punctuation() {
+        let table = Table::with_punctuation();
+        assert_eq!(
+            romaji_to_kana_with_table("ee, hontou desu ka?", &table),
+            "ええ、 ほんとう です か？"
+        );
+        assert_eq!(romaji_to_kana_with_table("nan~!", &table), "なん〜！");
+        assert_eq!(
+            RomajiToKanaConverter::with_table(&table).convert("[ko-hi-/kouhi-]."),
+            "「こーひー・こうひー」。"
+        );
+        // Without punctuation in the table, it is copied as is
+        assert_eq!(romaji_to_kana("ee, sou."), "ええ, そう.");
+    }
+
+    #[test]
+    fn test_try_romaji_to_kana() {
+        assert_eq!(try_romaji_to_kana("gakkou"), Ok("がっこう".to_string()));
+        assert_eq!(try_romaji_to_kana("ni hon"), Ok("に ほん".to_string()));
+        assert_eq!(
+            try_romaji_to_kana("konnichiha"),
+            Ok("こんにちは".to_string())
+        );
+        assert_eq!(try_romaji_to_kana(""), Ok(String::new()));
+        assert_eq!(
+            try_romaji_to_kana("tokyo!"),
+            Err(RomajiError::UnknownChar {
+                character: '!',
+                position: 5
+            })
+        );
+        assert_eq!(
+            try_romaji_to_kana("すしq"),
+            Err(RomajiError::UnknownChar {
+                character: 'す',
+                position: 0
+            })
+        );
+        assert_eq!(try_romaji_to_katakana("kafe"), Ok("カフェ".to_string()));
+    }
+
+    #[test]
+    fn test_with_warnings() {
+        let (kana, warnings) = romaji_to_kana_with_warnings("aq ka");
+        assert_eq!(kana, romaji_to_kana("aq ka"));
+        assert_eq!(
+            warnings,
+            [RomajiError::UnknownChar {
+                character: 'q',
+                position: 1
+            }]
+        );
+
+        let (kana, warnings) = romaji_to_kana_with_warnings("kitte");
+        assert_eq!(kana, "きって");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_aligned() {
+        let input = "konnichiha, tcha!";
+        let (kana, alignment) = romaji_to_kana_aligned(input);
+        assert_eq!(kana, romaji_to_kana(input));
+
+        let pieces: Vec<_> = alignment
+            .spans()
+            .iter()
+            .map(|span| (&input[span.source.clone()], &kana[span.output.clone()]))
+            .collect();
+        assert_eq!(
+            pieces,
+            [
+                ("ko", "こ"),
+                ("n", "ん"),
+                ("ni", "に"),
+                ("chi", "ち"),
+                ("ha", "は"),
+                (",", ","),
+                (" ", " "),
+                ("t", "っ"),
+                ("cha", "ちゃ"),
+                ("!", "!"),
+            ]
+        );
+
+        let start = kana.find("にち").unwrap();
+        assert_eq!(alignment.source_range(start..start + 6), 3..8);
+        assert_eq!(alignment.output_range(0..3), 0..6);
+    }
+
+    #[test]
+    fn test_converter() {
+        let mut converter = RomajiToKanaConverter::new();
+        assert_eq!(converter.convert("konnichiha"), "こんにちは");
+
+        let mut out = String::from("「");
+        converter.convert_into("gakkou", &mut out);
+        out.push('」');
+        assert_eq!(out, "「がっこう」");
+
+        let mut out = String::new();
+        converter.write_to("sushi", &mut out).unwrap();
+        assert_eq!(out, "すし");
+
+        let mut bytes = Vec::new();
+        RomajiToKanaConverter::katakana()
+            .write_io("ra-men", &mut bytes)
+            .unwrap();
+        assert_eq!(String::from_utf8(bytes).unwrap(), "ラーメン");
+
+        let mut table = Table::new();
+        table.insert_romaji("wi", "ゐ");
+        let mut converter = RomajiToKanaConverter::with_table(&table);
+        assert_eq!(converter.convert("wi"), "ゐ");
+    }
+
+    #[test]
+    fn test_converter_sound_mark_into_existing_buffer() {
+        // A sound mark must only compose with what this call wrote
+        let mut converter = RomajiToKanaConverter::new();
+        let mut out = String::from("か");
+        converter.convert_into("\u{3099}", &mut out);
+        assert_eq!(out, "か\u{3099}");
+
+        let mut out = String::from("か");
+        converter.convert_into("ka\u{3099}", &mut out);
+        assert_eq!(out, "かが");
+    }
+
+    #[test]
+    fn test_sound_marks() {
+        // Marks after kana, typed or copied through, compose with it
+        assert_eq!(romaji_to_kana("ka\u{3099}"), "が");
+        assert_eq!(romaji_to_katakana("ha゜n"), "パン");
+        assert_eq!(romaji_to_kana("か\u{3099}kko"), "がっこ");
+        assert_eq!(try_romaji_to_kana("ha\u{309A}"), Ok("ぱ".to_string()));
+
+        // A mark with nothing to compose with is copied and reported
+        let (kana, warnings) = romaji_to_kana_with_warnings("a\u{3099}");
+        assert_eq!(kana, "あ\u{3099}");
+        assert_eq!(
+            warnings,
+            [RomajiError::UnknownChar {
+                character: '\u{3099}',
+                position: 1
+            }]
+        );
+
+        // Small kana have no voiced form either
+        let (kana, warnings) = romaji_to_kana_with_warnings("xtu\u{3099}");
+        assert_eq!(kana, "っ\u{3099}");
+        assert_eq!(
+            warnings,
+            [RomajiError::UnknownChar {
+                character: '\u{3099}',
+                position: 3
+            }]
+        );
+
+        let (kana, alignment) = romaji_to_kana_aligned("ka\u{3099}ki");
+        assert_eq!(kana, "がき");
+        assert_eq!(alignment.source_range(0..3), 0..5);
+        assert_eq!(alignment.source_range(3..6), 5..7);
+    }
 }
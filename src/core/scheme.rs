@@ -0,0 +1,278 @@
+use super::kana_to_romaji::{
+    Case, KanaToRomajiConverter, LongVowel, NSeparator, PassThrough, Rules, Sokuon, System,
+    convert_kana_to_romaji,
+};
+use super::table::{Table, default_table};
+
+/// A custom romanization scheme built on top of one of the standard systems.
+///
+/// 標準の方式をもとに、個別のかなの綴りや促音・撥音・長音の書き方を変えた独自の方式です。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::{LongVowel, Sokuon, System};
+/// use buchikun::core::scheme::Scheme;
+///
+/// let scheme = Scheme::builder(System::Hepburn)
+///     .kana("ヲ", "o")
+///     .sokuon(Sokuon::Literal("q".to_string()))
+///     .m_before_labial(false)
+///     .long_vowel(LongVowel::Macron)
+///     .build();
+///
+/// assert_eq!(scheme.convert("ホッカイドウ"), "hoqkaidō");
+/// assert_eq!(scheme.convert("シンブンヲヨム"), "shinbun'oyomu");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scheme {
+    system: System,
+    table: Table,
+    long_vowel: LongVowel,
+    sokuon: Sokuon,
+    m_before_labial: bool,
+    case: Case,
+    pass_through: PassThrough,
+    n_separator: NSeparator,
+    phonetic_particles: bool,
+}
+
+impl Scheme {
+    /// Start a scheme from `system`, with the same behavior as [`kana_to_romaji`](crate::core::kana_to_romaji::kana_to_romaji).
+    pub fn builder(system: System) -> SchemeBuilder {
+        SchemeBuilder {
+            scheme: Scheme {
+                system,
+                table: default_table().clone(),
+                long_vowel: LongVowel::AsWritten,
+                sokuon: Sokuon::Double,
+                m_before_labial: system == System::Hepburn,
+                case: Case::Lower,
+                pass_through: PassThrough::Drop,
+                n_separator: NSeparator::Apostrophe,
+                phonetic_particles: false,
+            },
+        }
+    }
+
+    /// Convert Kana (Katakana or Hiragana) to Romaji with this scheme.
+    pub fn convert(&self, input: &str) -> String {
+        convert_kana_to_romaji(input, self.rules())
+    }
+
+    /// Reusable converter for this scheme, to convert into existing buffers.
+    pub fn converter(&self) -> KanaToRomajiConverter<'_> {
+        KanaToRomajiConverter::from_rules(self.rules())
+    }
+
+    fn rules(&self) -> Rules<'_> {
+        Rules {
+            table: &self.table,
+            long_vowel: self.long_vowel,
+            sokuon: &self.sokuon,
+            m_before_labial: self.m_before_labial,
+            case: self.case,
+            pass_through: self.pass_through,
+            n_separator: self.n_separator,
+            phonetic_particles: self.phonetic_particles,
+            ..Rules::new(self.system)
+        }
+    }
+}
+
+/// Builder for [`Scheme`], created by [`Scheme::builder`].
+#[derive(Debug, Clone)]
+pub struct SchemeBuilder {
+    scheme: Scheme,
+}
+
+impl SchemeBuilder {
+    /// Replace the base mapping table.
+    ///
+    /// Entries added with [`SchemeBuilder::kana`] before this call are discarded.
+    pub fn table(mut self, table: Table) -> Self {
+        self.scheme.table = table;
+        self
+    }
+
+    /// Override the spelling of `kana` (one or two Katakana).
+    ///
+    /// An empty spelling removes the entry, so a pair such as ティ is spelled kana by kana.
+    pub fn kana(mut self, kana: &str, romaji: &str) -> Self {
+        self.scheme.table.insert_kana(kana, romaji, romaji, romaji);
+        self
+    }
+
+    /// Set how the small tsu (ッ) is written.
+    pub fn sokuon(mut self, sokuon: Sokuon) -> Self {
+        self.scheme.sokuon = sokuon;
+        self
+    }
+
+    /// Write ン as "m" before b, m and p (the Hepburn default), or always as "n".
+    pub fn m_before_labial(mut self, enabled: bool) -> Self {
+        self.scheme.m_before_labial = enabled;
+        self
+    }
+
+    /// Set how long vowels are written.
+    pub fn long_vowel(mut self, long_vowel: LongVowel) -> Self {
+        self.scheme.long_vowel = long_vowel;
+        self
+    }
+
+    /// Set the letter case of the output.
+    pub fn case(mut self, case: Case) -> Self {
+        self.scheme.case = case;
+        self
+    }
+
+    /// Set what happens to characters that have no romaji.
+    pub fn pass_through(mut self, pass_through: PassThrough) -> Self {
+        self.scheme.pass_through = pass_through;
+        self
+    }
+
+    /// Set how ン is separated from a following vowel or y.
+    pub fn n_separator(mut self, n_separator: NSeparator) -> Self {
+        self.scheme.n_separator = n_separator;
+        self
+    }
+
+    /// Write the particles は, へ and を as pronounced: "wa", "e" and "o".
+    ///
+    /// See [`HepburnOptions::phonetic_particles`](crate::core::kana_to_romaji::HepburnOptions::phonetic_particles).
+    pub fn phonetic_particles(mut self, enabled: bool) -> Self {
+        self.scheme.phonetic_particles = enabled;
+        self
+    }
+
+    pub fn build(self) -> Scheme {
+        self.scheme
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::kana_to_romaji::{kana_to_romaji, kana_to_romaji_hepburn};
+
+    #[test]
+    fn test_default_matches_system() {
+        for system in [System::Hepburn, System::Kunrei, System::NihonShiki] {
+            let scheme = Scheme::builder(system).build();
+            for input in ["シンブン", "マッチャ", "キンエン", "トーキョー", "ヂャ"]
+            {
+                assert_eq!(scheme.convert(input), kana_to_romaji(input, system));
+            }
+        }
+    }
+
+    #[test]
+    fn test_kana_override() {
+        let scheme = Scheme::builder(System::Hepburn)
+            .kana("シ", "si")
+            .kana("シャ", "sya")
+            .kana("ティ", "")
+            .build();
+        assert_eq!(scheme.convert("シャシン"), "syasin");
+        assert_eq!(scheme.convert("ティ"), "tei");
+        // Overrides only apply to this scheme
+        assert_eq!(kana_to_romaji_hepburn("シャシン"), "shashin");
+    }
+
+    #[test]
+    fn test_sokuon() {
+        let double_first = Scheme::builder(System::Hepburn)
+            .sokuon(Sokuon::DoubleFirstLetter)
+            .build();
+        assert_eq!(double_first.convert("マッチャ"), "maccha");
+        assert_eq!(double_first.convert("カッパ"), "kappa");
+
+        let literal = Scheme::builder(System::Kunrei)
+            .sokuon(Sokuon::Literal("xtu".to_string()))
+            .build();
+        assert_eq!(literal.convert("カッパ"), "kaxtupa");
+        assert_eq!(literal.convert("アッ"), "axtu");
+    }
+
+    #[test]
+    fn test_n_before_labial() {
+        let hepburn_n = Scheme::builder(System::Hepburn)
+            .m_before_labial(false)
+            .build();
+        assert_eq!(hepburn_n.convert("シンブン"), "shinbun");
+
+        let kunrei_m = Scheme::builder(System::Kunrei)
+            .m_before_labial(true)
+            .build();
+        assert_eq!(kunrei_m.convert("シンブン"), "simbun");
+    }
+
+    #[test]
+    fn test_case() {
+        let upper = Scheme::builder(System::Kunrei).case(Case::Upper).build();
+        assert_eq!(upper.convert("シンブン"), "SINBUN");
+
+        let title = Scheme::builder(System::NihonShiki)
+            .case(Case::Title)
+            .build();
+        assert_eq!(title.convert("ヂ"), "Di");
+        let mut converter = title.converter();
+        assert_eq!(converter.convert("ツヅキ"), "Tuduki");
+    }
+
+    #[test]
+    fn test_pass_through() {
+        let scheme = Scheme::builder(System::Kunrei)
+            .pass_through(PassThrough::Keep)
+            .build();
+        assert_eq!(scheme.convert("SKU-シヨウ 42"), "SKU-siyou 42");
+    }
+
+    #[test]
+    fn test_n_separator() {
+        let hyphen = Scheme::builder(System::Hepburn)
+            .n_separator(NSeparator::Hyphen)
+            .build();
+        assert_eq!(hyphen.convert("キンエン"), "kin-en");
+        assert_eq!(hyphen.convert("ホンヤ"), "hon-ya");
+
+        let omit = Scheme::builder(System::Kunrei)
+            .n_separator(NSeparator::Omit)
+            .build();
+        assert_eq!(omit.convert("キンエン"), "kinen");
+    }
+
+    #[test]
+    fn test_phonetic_particles() {
+        let scheme = Scheme::builder(System::Kunrei)
+            .phonetic_particles(true)
+            .pass_through(PassThrough::Keep)
+            .build();
+        assert_eq!(scheme.convert("ちず を みる"), "tizu o miru");
+        assert_eq!(
+            Scheme::builder(System::Kunrei)
+                .build()
+                .convert("ちず を みる"),
+            "tizuwomiru"
+        );
+    }
+
+    #[test]
+    fn test_converter() {
+        let scheme = Scheme::builder(System::Hepburn).kana("シ", "si").build();
+        let mut converter = scheme.converter();
+        let mut out = String::new();
+        converter.convert_into("シシ", &mut out);
+        assert_eq!(out, "sisi");
+    }
+
+    #[test]
+    fn test_long_vowel() {
+        let scheme = Scheme::builder(System::Kunrei)
+            .long_vowel(LongVowel::Circumflex)
+            .build();
+        assert_eq!(scheme.convert("トウキョウ"), "tôkyô");
+    }
+}
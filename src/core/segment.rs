@@ -0,0 +1,187 @@
+use std::ops::Range;
+
+use super::classify::{Script, split_by_script};
+
+/// Words that are usually followed by a particle, recognized at the start of a Hiragana word.
+///
+/// Longer words come first, so that わたしたち is not read as わたし.
+const LEADING_WORDS: &[&str] = &[
+    "わたしたち",
+    "かのじょ",
+    "あそこ",
+    "わたし",
+    "あなた",
+    "かれ",
+    "ぼく",
+    "きみ",
+    "だれ",
+    "これ",
+    "それ",
+    "あれ",
+    "どれ",
+    "ここ",
+    "そこ",
+    "どこ",
+];
+
+/// Split `input` into words, with the particles は, へ and を as words of their own.
+///
+/// 文字列を単語に分割し、助詞の「は」「へ」「を」を切り出します。
+///
+/// Spaces separate words and are not returned. Script changes also start a word, but a Kanji
+/// or Katakana word keeps the Hiragana after it (送り仮名, 助動詞), except for the particles. There is no dictionary,
+/// so particles are recognized by rules:
+///
+/// - を after any kana or Kanji, as it is not used inside words.
+/// - は and へ right after Kanji or Katakana ("私は", "東京へ").
+/// - は and へ at the end of a Hiragana word of three or more kana ("わたしは、").
+/// - は after the particles に, で and と ("にほんには").
+/// - は, へ and を after a common pronoun such as わたし or これ ("わたしはがくせいです").
+///
+/// Other particles inside unspaced Hiragana are missed, and は, へ and を standing alone
+/// are always particles.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::segment::segment_words;
+///
+/// assert_eq!(segment_words("私は学生です。"), ["私", "は", "学生です", "。"]);
+/// assert_eq!(segment_words("ほんをよむ"), ["ほん", "を", "よむ"]);
+/// assert_eq!(segment_words("わたしはがくせいです"), ["わたし", "は", "がくせいです"]);
+/// assert_eq!(segment_words("はは へや"), ["はは", "へや"]);
+/// ```
+pub fn segment_words(input: &str) -> Vec<&str> {
+    word_ranges(input)
+        .into_iter()
+        .map(|range| &input[range])
+        .collect()
+}
+
+/// The byte ranges of the words of [`segment_words`].
+pub(crate) fn word_ranges(input: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    // A Kanji or Katakana word that the next Hiragana run may continue
+    let mut head: Option<Range<usize>> = None;
+    let mut run_start = 0;
+    for (script, run) in split_by_script(input) {
+        let run_range = run_start..run_start + run.len();
+        run_start = run_range.end;
+        if script == Script::Hiragana {
+            let word_start = head.take().map_or(run_range.start, |head| head.start);
+            split_particles(input, word_start, run_range, &mut words);
+            continue;
+        }
+        words.extend(head.take());
+        match script {
+            Script::Kanji | Script::Katakana => head = Some(run_range),
+            Script::Latin => words.push(run_range),
+            _ => {
+                // Each run of punctuation, digits, ... between spaces is a word
+                let mut word_start = None;
+                for (i, c) in run.char_indices().chain([(run.len(), ' ')]) {
+                    let at = run_range.start + i;
+                    match (c.is_whitespace(), word_start) {
+                        (true, Some(start)) => {
+                            words.push(start..at);
+                            word_start = None;
+                        }
+                        (false, None) => word_start = Some(at),
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    words.extend(head);
+    words
+}
+
+/// Push the words of `input[word_start..kana.end]`, where `kana` is a run of Hiragana and
+/// anything before it is Kanji or Katakana.
+fn split_particles(
+    input: &str,
+    word_start: usize,
+    kana: Range<usize>,
+    words: &mut Vec<Range<usize>>,
+) {
+    let mut start = word_start;
+    let mut previous = None;
+    for (i, c) in input[kana.clone()].char_indices() {
+        let at = kana.start + i;
+        let after = at + c.len_utf8();
+        let next = input[after..kana.end].chars().next();
+        let is_particle = matches!(c, 'は' | 'へ' | 'を')
+            // "は" + U+3099 is ば and "はー" is a long vowel
+            && !next.is_some_and(|n| matches!(n, 'ー' | '\u{3099}'..='\u{309C}'))
+            && if at == start {
+                after == kana.end
+            } else {
+                let before = &input[start..at];
+                c == 'を'
+                    || at == kana.start
+                    || (after == kana.end && before.chars().count() >= 2)
+                    || (c == 'は'
+                        && matches!(previous, Some('に' | 'で' | 'と'))
+                        && before.chars().count() >= 2)
+                    || LEADING_WORDS.contains(&before)
+            };
+        if is_particle {
+            if at > start {
+                words.push(start..at);
+            }
+            words.push(at..after);
+            start = after;
+        }
+        previous = Some(c);
+    }
+    if start < kana.end {
+        words.push(start..kana.end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segment_words() {
+        assert_eq!(
+            segment_words("わたし は がくせい です"),
+            ["わたし", "は", "がくせい", "です"]
+        );
+        assert_eq!(segment_words("コーヒーをのむ"), ["コーヒー", "を", "のむ"]);
+        assert_eq!(segment_words("東京へ行く"), ["東京", "へ", "行く"]);
+        assert_eq!(segment_words("がっこうへ。"), ["がっこう", "へ", "。"]);
+        assert_eq!(
+            segment_words("にほんにはやまがある"),
+            ["にほんに", "は", "やまがある"]
+        );
+        assert_eq!(
+            segment_words("ここへきてこれをみて"),
+            ["ここ", "へ", "きてこれ", "を", "みて"]
+        );
+        assert_eq!(
+            segment_words("わたしたちはABCを3つ"),
+            ["わたしたち", "は", "ABC", "を", "3", "つ"]
+        );
+        assert_eq!(segment_words("は、へ。を"), ["は", "、", "へ", "。", "を"]);
+        assert_eq!(segment_words(""), Vec::<&str>::new());
+        assert_eq!(segment_words("  "), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_not_particles() {
+        // Inside words and at their start
+        assert_eq!(segment_words("ごはんをたべる"), ["ごはん", "を", "たべる"]);
+        assert_eq!(segment_words("へいわ"), ["へいわ"]);
+        assert_eq!(segment_words("をとこ"), ["をとこ"]);
+        assert_eq!(segment_words("はは"), ["はは"]);
+        assert_eq!(segment_words("たいへん"), ["たいへん"]);
+        // Sound marks and ー belong to the kana before them
+        assert_eq!(segment_words("わたしは\u{3099}"), ["わたしは\u{3099}"]);
+        assert_eq!(segment_words("ほんをー"), ["ほんをー"]);
+        // Katakana ハ, ヘ and ヲ are not particles
+        assert_eq!(segment_words("ホンヲヨム"), ["ホンヲヨム"]);
+    }
+}
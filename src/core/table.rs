@@ -0,0 +1,858 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::kana_to_romaji::System;
+
+/// Kana and their spelling in each system: (kana, Hepburn, Kunrei, Nihon-shiki).
+///
+/// Keys are one Katakana, or two for contracted sounds (キャ) and foreign sounds (ティ).
+/// An empty spelling means the system has no entry, e.g. Hepburn writes クヮ as ク + ヮ.
+pub const KANA_TO_ROMAJI: &[(&str, &str, &str, &str)] = &[
+    ("ア", "a", "a", "a"),
+    ("イ", "i", "i", "i"),
+    ("ウ", "u", "u", "u"),
+    ("エ", "e", "e", "e"),
+    ("オ", "o", "o", "o"),
+    ("カ", "ka", "ka", "ka"),
+    ("キ", "ki", "ki", "ki"),
+    ("ク", "ku", "ku", "ku"),
+    ("ケ", "ke", "ke", "ke"),
+    ("コ", "ko", "ko", "ko"),
+    ("サ", "sa", "sa", "sa"),
+    ("シ", "shi", "si", "si"),
+    ("ス", "su", "su", "su"),
+    ("セ", "se", "se", "se"),
+    ("ソ", "so", "so", "so"),
+    ("タ", "ta", "ta", "ta"),
+    ("チ", "chi", "ti", "ti"),
+    ("ツ", "tsu", "tu", "tu"),
+    ("テ", "te", "te", "te"),
+    ("ト", "to", "to", "to"),
+    ("ナ", "na", "na", "na"),
+    ("ニ", "ni", "ni", "ni"),
+    ("ヌ", "nu", "nu", "nu"),
+    ("ネ", "ne", "ne", "ne"),
+    ("ノ", "no", "no", "no"),
+    ("ハ", "ha", "ha", "ha"),
+    ("ヒ", "hi", "hi", "hi"),
+    ("フ", "fu", "hu", "hu"),
+    ("ヘ", "he", "he", "he"),
+    ("ホ", "ho", "ho", "ho"),
+    ("マ", "ma", "ma", "ma"),
+    ("ミ", "mi", "mi", "mi"),
+    ("ム", "mu", "mu", "mu"),
+    ("メ", "me", "me", "me"),
+    ("モ", "mo", "mo", "mo"),
+    ("ヤ", "ya", "ya", "ya"),
+    ("ユ", "yu", "yu", "yu"),
+    ("ヨ", "yo", "yo", "yo"),
+    ("ラ", "ra", "ra", "ra"),
+    ("リ", "ri", "ri", "ri"),
+    ("ル", "ru", "ru", "ru"),
+    ("レ", "re", "re", "re"),
+    ("ロ", "ro", "ro", "ro"),
+    ("ワ", "wa", "wa", "wa"),
+    ("ヲ", "wo", "wo", "wo"),
+    ("ヰ", "i", "i", "wi"),
+    ("ヱ", "e", "e", "we"),
+    ("ン", "n", "n", "n"),
+    ("ガ", "ga", "ga", "ga"),
+    ("ギ", "gi", "gi", "gi"),
+    ("グ", "gu", "gu", "gu"),
+    ("ゲ", "ge", "ge", "ge"),
+    ("ゴ", "go", "go", "go"),
+    ("ザ", "za", "za", "za"),
+    ("ジ", "ji", "zi", "zi"),
+    ("ズ", "zu", "zu", "zu"),
+    ("ゼ", "ze", "ze", "ze"),
+    ("ゾ", "zo", "zo", "zo"),
+    ("ダ", "da", "da", "da"),
+    ("ヂ", "ji", "zi", "di"),
+    ("ヅ", "zu", "zu", "du"),
+    ("デ", "de", "de", "de"),
+    ("ド", "do", "do", "do"),
+    ("バ", "ba", "ba", "ba"),
+    ("ビ", "bi", "bi", "bi"),
+    ("ブ", "bu", "bu", "bu"),
+    ("ベ", "be", "be", "be"),
+    ("ボ", "bo", "bo", "bo"),
+    ("パ", "pa", "pa", "pa"),
+    ("ピ", "pi", "pi", "pi"),
+    ("プ", "pu", "pu", "pu"),
+    ("ペ", "pe", "pe", "pe"),
+    ("ポ", "po", "po", "po"),
+    ("ヴ", "vu", "vu", "vu"),
+    ("ァ", "a", "a", "a"),
+    ("ィ", "i", "i", "i"),
+    ("ゥ", "u", "u", "u"),
+    ("ェ", "e", "e", "e"),
+    ("ォ", "o", "o", "o"),
+    ("ャ", "ya", "ya", "ya"),
+    ("ュ", "yu", "yu", "yu"),
+    ("ョ", "yo", "yo", "yo"),
+    ("ヮ", "wa", "wa", "wa"),
+    ("ー", "-", "-", "-"),
+    ("キャ", "kya", "kya", "kya"),
+    ("キュ", "kyu", "kyu", "kyu"),
+    ("キョ", "kyo", "kyo", "kyo"),
+    ("シャ", "sha", "sya", "sya"),
+    ("シュ", "shu", "syu", "syu"),
+    ("ショ", "sho", "syo", "syo"),
+    ("チャ", "cha", "tya", "tya"),
+    ("チュ", "chu", "tyu", "tyu"),
+    ("チョ", "cho", "tyo", "tyo"),
+    ("ニャ", "nya", "nya", "nya"),
+    ("ニュ", "nyu", "nyu", "nyu"),
+    ("ニョ", "nyo", "nyo", "nyo"),
+    ("ヒャ", "hya", "hya", "hya"),
+    ("ヒュ", "hyu", "hyu", "hyu"),
+    ("ヒョ", "hyo", "hyo", "hyo"),
+    ("ミャ", "mya", "mya", "mya"),
+    ("ミュ", "myu", "myu", "myu"),
+    ("ミョ", "myo", "myo", "myo"),
+    ("リャ", "rya", "rya", "rya"),
+    ("リュ", "ryu", "ryu", "ryu"),
+    ("リョ", "ryo", "ryo", "ryo"),
+    ("ギャ", "gya", "gya", "gya"),
+    ("ギュ", "gyu", "gyu", "gyu"),
+    ("ギョ", "gyo", "gyo", "gyo"),
+    ("ジャ", "ja", "zya", "zya"),
+    ("ジュ", "ju", "zyu", "zyu"),
+    ("ジョ", "jo", "zyo", "zyo"),
+    ("ビャ", "bya", "bya", "bya"),
+    ("ビュ", "byu", "byu", "byu"),
+    ("ビョ", "byo", "byo", "byo"),
+    ("ピャ", "pya", "pya", "pya"),
+    ("ピュ", "pyu", "pyu", "pyu"),
+    ("ピョ", "pyo", "pyo", "pyo"),
+    ("ヂャ", "ja", "zya", "dya"),
+    ("ヂュ", "ju", "zyu", "dyu"),
+    ("ヂョ", "jo", "zyo", "dyo"),
+    ("ティ", "ti", "ti", "ti"),
+    ("ディ", "di", "di", "di"),
+    ("トゥ", "tu", "tu", "tu"),
+    ("ドゥ", "du", "du", "du"),
+    ("ファ", "fa", "fa", "fa"),
+    ("フィ", "fi", "fi", "fi"),
+    ("フェ", "fe", "fe", "fe"),
+    ("フォ", "fo", "fo", "fo"),
+    ("ウィ", "wi", "wi", "wi"),
+    ("ウェ", "we", "we", "we"),
+    ("ウォ", "wo", "wo", "wo"),
+    ("ヴァ", "va", "va", "va"),
+    ("ヴィ", "vi", "vi", "vi"),
+    ("ヴェ", "ve", "ve", "ve"),
+    ("ヴォ", "vo", "vo", "vo"),
+    ("シェ", "she", "sye", "sye"),
+    ("ジェ", "je", "zye", "zye"),
+    ("チェ", "che", "tye", "tye"),
+    ("クヮ", "", "", "kwa"),
+    ("グヮ", "", "", "gwa"),
+];
+
+/// Romaji and the Hiragana they are read as, sorted by length descending.
+///
+/// Covers the romaji table of common IMEs (Google / MS-IME), including
+/// Kunrei/Nihon-shiki spellings (si, tu, zi, di, du, ...) and x/l-prefixed small kana.
+pub const ROMAJI_TO_KANA: &[(&str, &str)] = &[
+    ("xtsu", "っ"),
+    ("ltsu", "っ"),
+    ("hwyu", "ふゅ"),
+    ("kya", "きゃ"),
+    ("kyi", "きぃ"),
+    ("kyu", "きゅ"),
+    ("kye", "きぇ"),
+    ("kyo", "きょ"),
+    ("gya", "ぎゃ"),
+    ("gyi", "ぎぃ"),
+    ("gyu", "ぎゅ"),
+    ("gye", "ぎぇ"),
+    ("gyo", "ぎょ"),
+    ("sya", "しゃ"),
+    ("syi", "しぃ"),
+    ("syu", "しゅ"),
+    ("sye", "しぇ"),
+    ("syo", "しょ"),
+    ("zya", "じゃ"),
+    ("zyi", "じぃ"),
+    ("zyu", "じゅ"),
+    ("zye", "じぇ"),
+    ("zyo", "じょ"),
+    ("jya", "じゃ"),
+    ("jyi", "じぃ"),
+    ("jyu", "じゅ"),
+    ("jye", "じぇ"),
+    ("jyo", "じょ"),
+    ("tya", "ちゃ"),
+    ("tyi", "ちぃ"),
+    ("tyu", "ちゅ"),
+    ("tye", "ちぇ"),
+    ("tyo", "ちょ"),
+    ("cya", "ちゃ"),
+    ("cyi", "ちぃ"),
+    ("cyu", "ちゅ"),
+    ("cye", "ちぇ"),
+    ("cyo", "ちょ"),
+    ("dya", "ぢゃ"),
+    ("dyi", "ぢぃ"),
+    ("dyu", "ぢゅ"),
+    ("dye", "ぢぇ"),
+    ("dyo", "ぢょ"),
+    ("nya", "にゃ"),
+    ("nyi", "にぃ"),
+    ("nyu", "にゅ"),
+    ("nye", "にぇ"),
+    ("nyo", "にょ"),
+    ("hya", "ひゃ"),
+    ("hyi", "ひぃ"),
+    ("hyu", "ひゅ"),
+    ("hye", "ひぇ"),
+    ("hyo", "ひょ"),
+    ("bya", "びゃ"),
+    ("byi", "びぃ"),
+    ("byu", "びゅ"),
+    ("bye", "びぇ"),
+    ("byo", "びょ"),
+    ("pya", "ぴゃ"),
+    ("pyi", "ぴぃ"),
+    ("pyu", "ぴゅ"),
+    ("pye", "ぴぇ"),
+    ("pyo", "ぴょ"),
+    ("mya", "みゃ"),
+    ("myi", "みぃ"),
+    ("myu", "みゅ"),
+    ("mye", "みぇ"),
+    ("myo", "みょ"),
+    ("rya", "りゃ"),
+    ("ryi", "りぃ"),
+    ("ryu", "りゅ"),
+    ("rye", "りぇ"),
+    ("ryo", "りょ"),
+    ("vya", "ゔゃ"),
+    ("vyi", "ゔぃ"),
+    ("vyu", "ゔゅ"),
+    ("vye", "ゔぇ"),
+    ("vyo", "ゔょ"),
+    ("fya", "ふゃ"),
+    ("fyi", "ふぃ"),
+    ("fyu", "ふゅ"),
+    ("fye", "ふぇ"),
+    ("fyo", "ふょ"),
+    ("hwa", "ふぁ"),
+    ("hwi", "ふぃ"),
+    ("hwe", "ふぇ"),
+    ("hwo", "ふぉ"),
+    ("sha", "しゃ"),
+    ("shu", "しゅ"),
+    ("she", "しぇ"),
+    ("sho", "しょ"),
+    ("cha", "ちゃ"),
+    ("chu", "ちゅ"),
+    ("che", "ちぇ"),
+    ("cho", "ちょ"),
+    ("shi", "し"),
+    ("chi", "ち"),
+    ("tsu", "つ"),
+    ("tsa", "つぁ"),
+    ("tsi", "つぃ"),
+    ("tse", "つぇ"),
+    ("tso", "つぉ"),
+    ("tha", "てゃ"),
+    ("thi", "てぃ"),
+    ("thu", "てゅ"),
+    ("the", "てぇ"),
+    ("tho", "てょ"),
+    ("dha", "でゃ"),
+    ("dhi", "でぃ"),
+    ("dhu", "でゅ"),
+    ("dhe", "でぇ"),
+    ("dho", "でょ"),
+    ("twa", "とぁ"),
+    ("twi", "とぃ"),
+    ("twu", "とぅ"),
+    ("twe", "とぇ"),
+    ("two", "とぉ"),
+    ("dwa", "どぁ"),
+    ("dwi", "どぃ"),
+    ("dwu", "どぅ"),
+    ("dwe", "どぇ"),
+    ("dwo", "どぉ"),
+    ("kwa", "くぁ"),
+    ("kwi", "くぃ"),
+    ("kwu", "くぅ"),
+    ("kwe", "くぇ"),
+    ("kwo", "くぉ"),
+    ("gwa", "ぐぁ"),
+    ("gwi", "ぐぃ"),
+    ("gwu", "ぐぅ"),
+    ("gwe", "ぐぇ"),
+    ("gwo", "ぐぉ"),
+    ("swa", "すぁ"),
+    ("swi", "すぃ"),
+    ("swu", "すぅ"),
+    ("swe", "すぇ"),
+    ("swo", "すぉ"),
+    ("fwa", "ふぁ"),
+    ("fwi", "ふぃ"),
+    ("fwu", "ふぅ"),
+    ("fwe", "ふぇ"),
+    ("fwo", "ふぉ"),
+    ("qwa", "くぁ"),
+    ("qwi", "くぃ"),
+    ("qwu", "くぅ"),
+    ("qwe", "くぇ"),
+    ("qwo", "くぉ"),
+    ("qya", "くゃ"),
+    ("qyi", "くぃ"),
+    ("qyu", "くゅ"),
+    ("qye", "くぇ"),
+    ("qyo", "くょ"),
+    ("wha", "うぁ"),
+    ("whi", "うぃ"),
+    ("whu", "う"),
+    ("whe", "うぇ"),
+    ("who", "うぉ"),
+    ("wyi", "ゐ"),
+    ("wye", "ゑ"),
+    ("xya", "ゃ"),
+    ("xyu", "ゅ"),
+    ("xyo", "ょ"),
+    ("xyi", "ぃ"),
+    ("xye", "ぇ"),
+    ("xtu", "っ"),
+    ("xwa", "ゎ"),
+    ("xka", "ゕ"),
+    ("xke", "ゖ"),
+    ("lya", "ゃ"),
+    ("lyu", "ゅ"),
+    ("lyo", "ょ"),
+    ("lyi", "ぃ"),
+    ("lye", "ぇ"),
+    ("ltu", "っ"),
+    ("lwa", "ゎ"),
+    ("lka", "ゕ"),
+    ("lke", "ゖ"),
+    ("ka", "か"),
+    ("ki", "き"),
+    ("ku", "く"),
+    ("ke", "け"),
+    ("ko", "こ"),
+    ("ga", "が"),
+    ("gi", "ぎ"),
+    ("gu", "ぐ"),
+    ("ge", "げ"),
+    ("go", "ご"),
+    ("sa", "さ"),
+    ("si", "し"),
+    ("su", "す"),
+    ("se", "せ"),
+    ("so", "そ"),
+    ("za", "ざ"),
+    ("zi", "じ"),
+    ("zu", "ず"),
+    ("ze", "ぜ"),
+    ("zo", "ぞ"),
+    ("ta", "た"),
+    ("ti", "ち"),
+    ("tu", "つ"),
+    ("te", "て"),
+    ("to", "と"),
+    ("da", "だ"),
+    ("di", "ぢ"),
+    ("du", "づ"),
+    ("de", "で"),
+    ("do", "ど"),
+    ("na", "な"),
+    ("ni", "に"),
+    ("nu", "ぬ"),
+    ("ne", "ね"),
+    ("no", "の"),
+    ("ha", "は"),
+    ("hi", "ひ"),
+    ("hu", "ふ"),
+    ("he", "へ"),
+    ("ho", "ほ"),
+    ("ba", "ば"),
+    ("bi", "び"),
+    ("bu", "ぶ"),
+    ("be", "べ"),
+    ("bo", "ぼ"),
+    ("pa", "ぱ"),
+    ("pi", "ぴ"),
+    ("pu", "ぷ"),
+    ("pe", "ぺ"),
+    ("po", "ぽ"),
+    ("ma", "ま"),
+    ("mi", "み"),
+    ("mu", "む"),
+    ("me", "め"),
+    ("mo", "も"),
+    ("ra", "ら"),
+    ("ri", "り"),
+    ("ru", "る"),
+    ("re", "れ"),
+    ("ro", "ろ"),
+    ("ya", "や"),
+    ("yi", "い"),
+    ("yu", "ゆ"),
+    ("ye", "いぇ"),
+    ("yo", "よ"),
+    ("wa", "わ"),
+    ("wi", "うぃ"),
+    ("wu", "う"),
+    ("we", "うぇ"),
+    ("wo", "を"),
+    ("ja", "じゃ"),
+    ("ji", "じ"),
+    ("ju", "じゅ"),
+    ("je", "じぇ"),
+    ("jo", "じょ"),
+    ("fa", "ふぁ"),
+    ("fi", "ふぃ"),
+    ("fu", "ふ"),
+    ("fe", "ふぇ"),
+    ("fo", "ふぉ"),
+    ("va", "ゔぁ"),
+    ("vi", "ゔぃ"),
+    ("vu", "ゔ"),
+    ("ve", "ゔぇ"),
+    ("vo", "ゔぉ"),
+    ("ca", "か"),
+    ("ci", "し"),
+    ("cu", "く"),
+    ("ce", "せ"),
+    ("co", "こ"),
+    ("qa", "くぁ"),
+    ("qi", "くぃ"),
+    ("qu", "く"),
+    ("qe", "くぇ"),
+    ("qo", "くぉ"),
+    ("xa", "ぁ"),
+    ("xi", "ぃ"),
+    ("xu", "ぅ"),
+    ("xe", "ぇ"),
+    ("xo", "ぉ"),
+    ("la", "ぁ"),
+    ("li", "ぃ"),
+    ("lu", "ぅ"),
+    ("le", "ぇ"),
+    ("lo", "ぉ"),
+    ("xn", "ん"),
+    ("n'", "ん"),
+    ("a", "あ"),
+    ("i", "い"),
+    ("u", "う"),
+    ("e", "え"),
+    ("o", "お"),
+    ("n", "ん"),
+    ("-", "ー"),
+];
+
+/// ASCII punctuation and the Japanese punctuation an IME types for it: (ASCII, Japanese).
+///
+/// Not part of [`Table::new`]; add them with [`Table::insert_punctuation`] to convert
+/// punctuation in both directions.
+pub const PUNCTUATION: &[(&str, &str)] = &[
+    (".", "。"),
+    (",", "、"),
+    ("[", "「"),
+    ("]", "」"),
+    ("/", "・"),
+    ("~", "〜"),
+    ("!", "！"),
+    ("?", "？"),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TableError {
+    /// The line (1-based) does not have the expected number of columns.
+    InvalidLine { line: usize },
+}
+
+/// Mapping between kana and romaji used by both `kana_to_romaji` and `romaji_to_kana`.
+///
+/// [`Table::new`] (or `Table::default()`) starts from the built-in tables
+/// [`KANA_TO_ROMAJI`] and [`ROMAJI_TO_KANA`]; entries can then be added or replaced
+/// at runtime, one by one or from text.
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::kana_to_romaji::{kana_to_romaji_with_table, System};
+/// use buchikun::core::romaji_to_kana::romaji_to_kana_with_table;
+/// use buchikun::core::table::Table;
+///
+/// let mut table = Table::new();
+/// table.insert_kana("ヴァ", "ba", "ba", "ba");
+/// table.insert_romaji("wi", "ゐ");
+///
+/// assert_eq!(kana_to_romaji_with_table("ヴァイオリン", System::Hepburn, &table), "baiorin");
+/// assert_eq!(romaji_to_kana_with_table("wi", &table), "ゐ");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Table {
+    kana: HashMap<String, [String; 3]>,
+    romaji: Trie,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Table::new()
+    }
+}
+
+impl Table {
+    /// Create a table with the built-in mappings.
+    pub fn new() -> Self {
+        let mut table = Table::empty();
+        for (kana, hepburn, kunrei, nihon_shiki) in KANA_TO_ROMAJI {
+            table.insert_kana(kana, hepburn, kunrei, nihon_shiki);
+        }
+        for (romaji, kana) in ROMAJI_TO_KANA {
+            table.insert_romaji(romaji, kana);
+        }
+        table
+    }
+
+    /// Create a table with the built-in mappings and [`PUNCTUATION`].
+    ///
+    /// ```
+    /// use buchikun::core::kana_to_romaji::{kana_to_romaji_with_table, System};
+    /// use buchikun::core::romaji_to_kana::romaji_to_kana_with_table;
+    /// use buchikun::core::table::Table;
+    ///
+    /// let table = Table::with_punctuation();
+    /// assert_eq!(romaji_to_kana_with_table("[hai], sou desu.", &table), "「はい」、 そう です。");
+    /// assert_eq!(kana_to_romaji_with_table("「はい」、そうです。", System::Hepburn, &table), "[hai],soudesu.");
+    /// ```
+    pub fn with_punctuation() -> Self {
+        let mut table = Table::new();
+        table.insert_punctuation();
+        table
+    }
+
+    /// Add [`PUNCTUATION`] in both directions.
+    ///
+    /// Entries can be replaced afterwards like any other, e.g. to write "・" as a space.
+    pub fn insert_punctuation(&mut self) {
+        for (ascii, japanese) in PUNCTUATION {
+            self.insert_kana(japanese, ascii, ascii, ascii);
+            self.insert_romaji(ascii, japanese);
+        }
+    }
+
+    /// Create a table without any mappings.
+    pub fn empty() -> Self {
+        Table {
+            kana: HashMap::new(),
+            romaji: Trie::new(),
+        }
+    }
+
+    /// Add or replace the spelling of `kana` (one or two Katakana) in each system.
+    ///
+    /// Pass an empty spelling for systems that should not combine the kana.
+    pub fn insert_kana(&mut self, kana: &str, hepburn: &str, kunrei: &str, nihon_shiki: &str) {
+        self.kana.insert(
+            kana.to_string(),
+            [
+                hepburn.to_string(),
+                kunrei.to_string(),
+                nihon_shiki.to_string(),
+            ],
+        );
+    }
+
+    /// Add or replace the kana that `romaji` is read as.
+    pub fn insert_romaji(&mut self, romaji: &str, kana: &str) {
+        self.romaji.insert(romaji, kana);
+    }
+
+    /// Add kana entries from text, one per line.
+    ///
+    /// Each line is either `kana romaji` (same spelling in every system) or
+    /// `kana hepburn kunrei nihon_shiki`, separated by whitespace.
+    /// Empty lines and lines starting with `#` are ignored.
+    ///
+    /// ```
+    /// use buchikun::core::table::Table;
+    /// use buchikun::core::kana_to_romaji::System;
+    ///
+    /// let mut table = Table::new();
+    /// table.load_kana("# Ainu\nㇰ k\nトゥ tu tu tu\n").unwrap();
+    /// assert_eq!(table.romaji("ㇰ", System::Kunrei), Some("k"));
+    /// ```
+    pub fn load_kana(&mut self, text: &str) -> Result<(), TableError> {
+        for (line, columns) in parse_lines(text) {
+            match columns[..] {
+                [kana, romaji] => self.insert_kana(kana, romaji, romaji, romaji),
+                [kana, hepburn, kunrei, nihon_shiki] => {
+                    self.insert_kana(kana, hepburn, kunrei, nihon_shiki)
+                }
+                _ => return Err(TableError::InvalidLine { line }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Add romaji entries from text, one `romaji kana` pair per line.
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn load_romaji(&mut self, text: &str) -> Result<(), TableError> {
+        for (line, columns) in parse_lines(text) {
+            match columns[..] {
+                [romaji, kana] => self.insert_romaji(romaji, kana),
+                _ => return Err(TableError::InvalidLine { line }),
+            }
+        }
+        Ok(())
+    }
+
+    /// Spelling of `kana` (one or two Katakana) in `system`, if any.
+    pub fn romaji(&self, kana: &str, system: System) -> Option<&str> {
+        let spellings = self.kana.get(kana)?;
+        let romaji = match system {
+            System::Hepburn => &spellings[0],
+            System::Kunrei => &spellings[1],
+            System::NihonShiki => &spellings[2],
+        };
+        Some(romaji.as_str()).filter(|r| !r.is_empty())
+    }
+
+    /// Longest romaji entry at the start of `s`, as (length in bytes, Hiragana).
+    ///
+    /// Romaji is matched ignoring ASCII case.
+    pub fn match_romaji(&self, s: &str) -> Option<(usize, &str)> {
+        self.romaji.longest_prefix(s)
+    }
+
+    /// Whether some romaji entry is longer than `s` and starts with it ("k" for "ka").
+    pub fn has_longer_romaji(&self, s: &str) -> bool {
+        self.romaji.has_longer(s)
+    }
+}
+
+/// Byte-wise prefix tree over the romaji entries.
+///
+/// Looking up the longest entry at a position walks at most as many nodes as
+/// the longest key (4 bytes for the built-in table), instead of scanning every entry.
+#[derive(Debug, Clone)]
+struct Trie {
+    // nodes[0] is the root.
+    nodes: Vec<TrieNode>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TrieNode {
+    // Few enough (at most one per ASCII letter) that a linear scan beats hashing.
+    children: Vec<(u8, usize)>,
+    kana: Option<String>,
+}
+
+impl Trie {
+    fn new() -> Self {
+        Trie {
+            nodes: vec![TrieNode::default()],
+        }
+    }
+
+    fn insert(&mut self, key: &str, kana: &str) {
+        let mut node = 0;
+        for &byte in key.as_bytes() {
+            node = match self.child(node, byte) {
+                Some(child) => child,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let child = self.nodes.len() - 1;
+                    let byte = byte.to_ascii_lowercase();
+                    self.nodes[node].children.push((byte, child));
+                    child
+                }
+            };
+        }
+        self.nodes[node].kana = Some(kana.to_string());
+    }
+
+    fn longest_prefix(&self, s: &str) -> Option<(usize, &str)> {
+        let mut node = 0;
+        let mut found = None;
+        for (i, &byte) in s.as_bytes().iter().enumerate() {
+            match self.child(node, byte) {
+                Some(child) => node = child,
+                None => break,
+            }
+            if let Some(kana) = &self.nodes[node].kana {
+                found = Some((i + 1, kana.as_str()));
+            }
+        }
+        found
+    }
+
+    fn has_longer(&self, s: &str) -> bool {
+        let mut node = 0;
+        for &byte in s.as_bytes() {
+            match self.child(node, byte) {
+                Some(child) => node = child,
+                None => return false,
+            }
+        }
+        !self.nodes[node].children.is_empty()
+    }
+
+    fn child(&self, node: usize, byte: u8) -> Option<usize> {
+        // Keys are stored in lowercase and matched ignoring ASCII case.
+        let byte = byte.to_ascii_lowercase();
+        self.nodes[node]
+            .children
+            .iter()
+            .find(|(b, _)| *b == byte)
+            .map(|(_, child)| *child)
+    }
+}
+
+/// Table shared by the conversion functions that do not take one.
+pub(crate) fn default_table() -> &'static Table {
+    static TABLE: OnceLock<Table> = OnceLock::new();
+    TABLE.get_or_init(Table::new)
+}
+
+fn parse_lines(text: &str) -> impl Iterator<Item = (usize, Vec<&str>)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(i, line)| (i, line.split_whitespace().collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin() {
+        let table = Table::new();
+        assert_eq!(table.romaji("シ", System::Hepburn), Some("shi"));
+        assert_eq!(table.romaji("シ", System::Kunrei), Some("si"));
+        assert_eq!(table.romaji("ヂ", System::NihonShiki), Some("di"));
+        assert_eq!(table.romaji("クヮ", System::Hepburn), None);
+        assert_eq!(table.romaji("クヮ", System::NihonShiki), Some("kwa"));
+        assert_eq!(table.romaji("漢", System::Hepburn), None);
+
+        assert_eq!(table.match_romaji("kyouto"), Some((3, "きょ")));
+        assert_eq!(table.match_romaji("xtsu"), Some((4, "っ")));
+        assert_eq!(table.match_romaji("!"), None);
+        assert_eq!(table.match_romaji("hwyu"), Some((4, "ふゅ")));
+        assert_eq!(table.match_romaji("hwo"), Some((3, "ふぉ")));
+        assert_eq!(table.match_romaji("qyo"), Some((3, "くょ")));
+
+        assert!(table.has_longer_romaji("k"));
+        assert!(table.has_longer_romaji("ky"));
+        assert!(table.has_longer_romaji("hwy"));
+        assert!(!table.has_longer_romaji("kya"));
+        assert!(!table.has_longer_romaji("kk"));
+        assert!(table.has_longer_romaji(""));
+
+        assert_eq!(table.match_romaji("KYA"), Some((3, "きゃ")));
+        assert!(table.has_longer_romaji("K"));
+    }
+
+    #[test]
+    fn test_builtin_romaji_sorted() {
+        for pair in ROMAJI_TO_KANA.windows(2) {
+            assert!(pair[0].0.len() >= pair[1].0.len(), "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn test_trie_matches_linear_scan() {
+        // The trie must agree with scanning ROMAJI_TO_KANA from the longest entry down.
+        let linear = |s: &str| {
+            ROMAJI_TO_KANA
+                .iter()
+                .find(|(romaji, _)| s.starts_with(romaji))
+                .map(|(romaji, kana)| (romaji.len(), *kana))
+        };
+        let table = Table::new();
+        let letters = "abcdefghijklmnopqrstuvwxyz'-";
+        for a in letters.chars() {
+            for b in letters.chars() {
+                for c in letters.chars() {
+                    let s = format!("{}{}{}u", a, b, c);
+                    for start in 0..s.len() {
+                        assert_eq!(
+                            table.match_romaji(&s[start..]),
+                            linear(&s[start..]),
+                            "{}",
+                            &s[start..]
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut table = Table::new();
+        table.insert_kana("シ", "si", "si", "si");
+        assert_eq!(table.romaji("シ", System::Hepburn), Some("si"));
+
+        table.insert_romaji("wi", "ゐ");
+        table.insert_romaji("kwa", "くゎ");
+        assert_eq!(table.match_romaji("wi"), Some((2, "ゐ")));
+        assert_eq!(table.match_romaji("kwa"), Some((3, "くゎ")));
+        // Longer entries still win over shorter ones
+        table.insert_romaji("k", "く");
+        assert_eq!(table.match_romaji("ka"), Some((2, "か")));
+        assert_eq!(table.match_romaji("kt"), Some((1, "く")));
+        // Non-ASCII keys work too
+        table.insert_romaji("ā", "ああ");
+        assert_eq!(table.match_romaji("ākā"), Some(("ā".len(), "ああ")));
+    }
+
+    #[test]
+    fn test_punctuation() {
+        let table = Table::new();
+        assert_eq!(table.romaji("。", System::Hepburn), None);
+        assert_eq!(table.match_romaji("."), None);
+
+        let table = Table::with_punctuation();
+        for (ascii, japanese) in PUNCTUATION {
+            for system in [System::Hepburn, System::Kunrei, System::NihonShiki] {
+                assert_eq!(table.romaji(japanese, system), Some(*ascii));
+            }
+            assert_eq!(table.match_romaji(ascii), Some((ascii.len(), *japanese)));
+        }
+        // The built-in entries are still there
+        assert_eq!(table.match_romaji("-"), Some((1, "ー")));
+        assert_eq!(table.romaji("ー", System::Hepburn), Some("-"));
+
+        let mut table = Table::with_punctuation();
+        table.insert_kana("・", " ", " ", " ");
+        assert_eq!(table.romaji("・", System::Hepburn), Some(" "));
+        assert_eq!(table.match_romaji("/"), Some((1, "・")));
+    }
+
+    #[test]
+    fn test_load() {
+        let mut table = Table::empty();
+        assert_eq!(
+            table.load_kana("# comment\n\nㇰ k\nヴァ va ba ba\n"),
+            Ok(())
+        );
+        assert_eq!(table.romaji("ㇰ", System::NihonShiki), Some("k"));
+        assert_eq!(table.romaji("ヴァ", System::Kunrei), Some("ba"));
+
+        assert_eq!(table.load_romaji("va ゔぁ"), Ok(()));
+        assert_eq!(table.match_romaji("va"), Some((2, "ゔぁ")));
+
+        assert_eq!(
+            table.load_kana("ア a\nイ i u\n"),
+            Err(TableError::InvalidLine { line: 2 })
+        );
+        assert_eq!(
+            table.load_romaji("a"),
+            Err(TableError::InvalidLine { line: 1 })
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use super::gojuon::{Row, devoice, position};
+use super::kana::{compose_sound_marks, push_composed};
+
+/// The first half-width Katakana or punctuation, ｡. They run to ﾟ (U+FF9F).
+const HALF_WIDTH_START: u32 = 0xFF61;
+
+/// The full-width counterparts of the half-width Katakana and punctuation, in code point order
+/// (｡｢｣､･ｦｧ ... ﾜﾝﾞﾟ).
+const FULL_WIDTH_KANA: [char; 63] = [
+    '。', '「', '」', '、', '・', 'ヲ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ', 'ャ', 'ュ', 'ョ', 'ッ', 'ー',
+    'ア', 'イ', 'ウ', 'エ', 'オ', 'カ', 'キ', 'ク', 'ケ', 'コ', 'サ', 'シ', 'ス', 'セ', 'ソ', 'タ',
+    'チ', 'ツ', 'テ', 'ト', 'ナ', 'ニ', 'ヌ', 'ネ', 'ノ', 'ハ', 'ヒ', 'フ', 'ヘ', 'ホ', 'マ', 'ミ',
+    'ム', 'メ', 'モ', 'ヤ', 'ユ', 'ヨ', 'ラ', 'リ', 'ル', 'レ', 'ロ', 'ワ', 'ン', '゛', '゜',
+];
+
+/// The first full-width char with a half-width form, 、.
+const FULL_WIDTH_START: u32 = 0x3001;
+
+/// The half-width form of each char from 、 to ー (U+30FC), or '\0' if it has none.
+const HALF_WIDTH_KANA: [char; 0x30FC - FULL_WIDTH_START as usize + 1] = {
+    let mut table = ['\0'; 0x30FC - FULL_WIDTH_START as usize + 1];
+    let mut i = 0;
+    while i < FULL_WIDTH_KANA.len() {
+        let half = char::from_u32(HALF_WIDTH_START + i as u32).unwrap();
+        table[(FULL_WIDTH_KANA[i] as u32 - FULL_WIDTH_START) as usize] = half;
+        i += 1;
+    }
+    // Combining marks become the spacing half-width ones
+    table[(0x3099 - FULL_WIDTH_START) as usize] = HALF_WIDTH_DAKUTEN;
+    table[(0x309A - FULL_WIDTH_START) as usize] = HALF_WIDTH_HANDAKUTEN;
+    table
+};
+
+const HALF_WIDTH_DAKUTEN: char = 'ﾞ';
+const HALF_WIDTH_HANDAKUTEN: char = 'ﾟ';
+
+/// Normalize text to full-width Kana and half-width ASCII.
+///
+/// 半角カナを全角カナに、全角英数字・記号を半角に変換します。
+///
+/// Kana followed by a (han)dakuten are composed into one character, whether the mark is
+/// half-width (ｶﾞ -> ガ), combining (U+3099) or spacing (゛). Full-width ASCII
+/// (Ａ, １, ！, ...) becomes ASCII and the ideographic space becomes a space.
+/// This is the form [`kana_to_romaji`] expects.
+///
+/// [`kana_to_romaji`]: crate::core::kana_to_romaji::kana_to_romaji
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::width::normalize_width;
+/// assert_eq!(normalize_width("ｶﾀｶﾅ"), "カタカナ");
+/// assert_eq!(normalize_width("ｶﾞｯｺｳ ﾊﾟﾝ"), "ガッコウ パン");
+/// assert_eq!(normalize_width("ＡＢＣ１２３"), "ABC123");
+/// ```
+pub fn normalize_width(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    for c in input.chars() {
+        let c = half_width_kana_to_full(c)
+            .or_else(|| full_width_ascii_to_half(c))
+            .unwrap_or(c);
+        push_composed(&mut result, c);
+    }
+    result
+}
+
+/// Convert to half-width Katakana and ASCII, for fixed-width legacy systems.
+///
+/// 全角カタカナ・記号を半角カナに、全角英数字を半角に変換します。
+///
+/// Voiced Katakana are written with a separate half-width mark (ガ -> ｶﾞ), and so are
+/// combining and spacing marks (カ\u{3099} -> ｶﾞ).
+/// Katakana without a half-width form (ヰ, ヱ, ヮ, ヵ, ヶ) and Hiragana are returned as is;
+/// convert Hiragana with [`hiragana_to_katakana`] first if needed.
+///
+/// [`hiragana_to_katakana`]: crate::core::kana::hiragana_to_katakana
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::width::to_half_width;
+/// assert_eq!(to_half_width("ガッコウ　パン"), "ｶﾞｯｺｳ ﾊﾟﾝ");
+/// assert_eq!(to_half_width("ＡＢＣ－１２３"), "ABC-123");
+/// ```
+pub fn to_half_width(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    for c in compose_sound_marks(input).chars() {
+        if let Some((base, mark)) = decompose(c) {
+            result.push(base);
+            result.push(mark);
+        } else if let Some(kana) = full_width_kana_to_half(c) {
+            result.push(kana);
+        } else {
+            result.push(full_width_ascii_to_half(c).unwrap_or(c));
+        }
+    }
+    result
+}
+
+/// Convert to full-width Katakana and full-width ASCII.
+///
+/// 半角カナを全角カナに、半角英数字・記号を全角に変換します。
+///
+/// # Examples
+///
+/// ```
+/// use buchikun::core::width::to_full_width;
+/// assert_eq!(to_full_width("ｶﾞｯｺｳ ﾊﾟﾝ"), "ガッコウ　パン");
+/// assert_eq!(to_full_width("ABC-123"), "ＡＢＣ－１２３");
+/// ```
+pub fn to_full_width(input: &str) -> String {
+    normalize_width(input)
+        .chars()
+        .map(|c| ascii_to_full_width(c).unwrap_or(c))
+        .collect()
+}
+
+fn half_width_kana_to_full(c: char) -> Option<char> {
+    match c {
+        '｡'..='ﾟ' => Some(FULL_WIDTH_KANA[(c as u32 - HALF_WIDTH_START) as usize]),
+        _ => None,
+    }
+}
+
+fn full_width_kana_to_half(c: char) -> Option<char> {
+    match c {
+        '、'..='ー' => {
+            Some(HALF_WIDTH_KANA[(c as u32 - FULL_WIDTH_START) as usize]).filter(|&c| c != '\0')
+        }
+        _ => None,
+    }
+}
+
+fn full_width_ascii_to_half(c: char) -> Option<char> {
+    match c {
+        // U+FF01 ..= U+FF5E mirror ! ..= ~ at an offset of 0xFEE0.
+        '！'..='～' => char::from_u32(c as u32 - 0xFEE0),
+        '\u{3000}' => Some(' '),
+        _ => None,
+    }
+}
+
+fn ascii_to_full_width(c: char) -> Option<char> {
+    match c {
+        '!'..='~' => char::from_u32(c as u32 + 0xFEE0),
+        ' ' => Some('\u{3000}'),
+        _ => None,
+    }
+}
+
+/// Split a voiced full-width Katakana into its half-width base and mark.
+fn decompose(c: char) -> Option<(char, char)> {
+    let base = full_width_kana_to_half(devoice(c)?)?;
+    let mark = match position(c) {
+        Some((Row::Pa, _)) => HALF_WIDTH_HANDAKUTEN,
+        _ => HALF_WIDTH_DAKUTEN,
+    };
+    Some((base, mark))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables_line_up() {
+        let half_width: String = ('｡'..='ﾟ').collect();
+        assert_eq!(
+            half_width,
+            "｡｢｣､･ｦｧｨｩｪｫｬｭｮｯｰｱｲｳｴｵｶｷｸｹｺｻｼｽｾｿﾀﾁﾂﾃﾄﾅﾆﾇﾈﾉﾊﾋﾌﾍﾎﾏﾐﾑﾒﾓﾔﾕﾖﾗﾘﾙﾚﾛﾜﾝﾞﾟ"
+        );
+        for (half, full) in half_width.chars().zip(FULL_WIDTH_KANA) {
+            assert_eq!(half_width_kana_to_full(half), Some(full));
+            assert_eq!(full_width_kana_to_half(full), Some(half));
+        }
+        assert_eq!(full_width_kana_to_half('ヰ'), None);
+        assert_eq!(full_width_kana_to_half('あ'), None);
+        assert_eq!(full_width_kana_to_half('\0'), None);
+    }
+
+    #[test]
+    fn test_normalize_width() {
+        assert_eq!(normalize_width("ｱｲｳｴｵ"), "アイウエオ");
+        assert_eq!(normalize_width("ｶﾞｷﾞｸﾞｹﾞｺﾞ"), "ガギグゲゴ");
+        assert_eq!(normalize_width("ﾊﾞﾋﾟﾌﾞﾍﾟﾎﾞ"), "バピブペボ");
+        assert_eq!(normalize_width("ｳﾞｧｲｵﾘﾝ"), "ヴァイオリン");
+        assert_eq!(normalize_width("ﾜﾞｦﾞ"), "ヷヺ");
+        assert_eq!(normalize_width("｢ｺｰﾋｰ･ﾃｨｰ｣｡"), "「コーヒー・ティー」。");
+        assert_eq!(normalize_width("ＡＢＣ　１２３！？"), "ABC 123!?");
+        assert_eq!(normalize_width("ひらがな漢字"), "ひらがな漢字");
+    }
+
+    #[test]
+    fn test_marks_that_do_not_combine() {
+        // A mark on a kana that has no voiced form is kept as a separate full-width mark
+        assert_eq!(normalize_width("ｱﾞ"), "ア゛");
+        assert_eq!(normalize_width("ｶﾟ"), "カ゜");
+        assert_eq!(normalize_width("ﾞ"), "゛");
+        assert_eq!(normalize_width("ﾊﾟﾞ"), "パ゛");
+        assert_eq!(normalize_width("ｯﾞｧﾟ"), "ッ゛ァ゜");
+    }
+
+    #[test]
+    fn test_combining_and_spacing_marks() {
+        assert_eq!(normalize_width("カ\u{3099}ハ\u{309A}"), "ガパ");
+        assert_eq!(normalize_width("か゛ﾊ゜"), "がパ");
+        assert_eq!(to_half_width("カ\u{3099}ハ゜"), "ｶﾞﾊﾟ");
+        assert_eq!(to_half_width("ア\u{3099}"), "ｱﾞ");
+        assert_eq!(to_full_width("ｶ\u{3099}"), "ガ");
+    }
+
+    #[test]
+    fn test_to_half_width() {
+        assert_eq!(to_half_width("アイウエオ"), "ｱｲｳｴｵ");
+        assert_eq!(to_half_width("ガギグゲゴパピプペポヴ"), "ｶﾞｷﾞｸﾞｹﾞｺﾞﾊﾟﾋﾟﾌﾟﾍﾟﾎﾟｳﾞ");
+        assert_eq!(to_half_width("「ヂヅ」、ヷヺ。"), "｢ﾁﾞﾂﾞ｣､ﾜﾞｦﾞ｡");
+        assert_eq!(to_half_width("ヰヱヮヵヶ"), "ヰヱヮヵヶ");
+        assert_eq!(to_half_width("かな"), "かな");
+        assert_eq!(to_half_width("ｶﾅ"), "ｶﾅ");
+    }
+
+    #[test]
+    fn test_to_full_width() {
+        assert_eq!(to_full_width("ﾔﾏﾀﾞ ﾀﾛｳ"), "ヤマダ　タロウ");
+        assert_eq!(to_full_width("Tel: 03-1234"), "Ｔｅｌ：　０３－１２３４");
+        assert_eq!(to_full_width("ＡＢＣ"), "ＡＢＣ");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let full = "ガッコウ　パン「ヴァイオリン」";
+        assert_eq!(to_full_width(&to_half_width(full)), full);
+        let half = "ｶﾞｯｺｳ ﾊﾟﾝ｢ｳﾞｧｲｵﾘﾝ｣";
+        assert_eq!(to_half_width(&normalize_width(half)), half);
+    }
+}
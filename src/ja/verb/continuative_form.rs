@@ -1,4 +1,6 @@
-use super::infer_conjugation_type::{ConjugationType, VerbError};
+use super::infer_conjugation_type::{ConjugationType, VerbError, godan_ending};
+use crate::core::gojuon::Column;
+use crate::core::kana::compose_sound_marks;
 
 /// Conjugate a Japanese verb to its Continuative form (Ren'youkei).
 ///
@@ -32,6 +34,9 @@ pub fn continuative_form(verb: &str, conjugation: ConjugationType) -> Result<Str
         return Err(VerbError::NotAVerb);
     }
 
+    // Decomposed input ("く" + U+3099) conjugates like its composed form
+    let verb = compose_sound_marks(verb);
+    let verb = verb.as_str();
     let chars: Vec<char> = verb.chars().collect();
     let len = chars.len();
 
@@ -45,19 +50,7 @@ pub fn continuative_form(verb: &str, conjugation: ConjugationType) -> Result<Str
             let last_char = chars[len - 1];
             let stem = &verb[..verb.len() - last_char.len_utf8()];
 
-            let new_ending = match last_char {
-                'う' => "い",
-                'く' => "き",
-                'ぐ' => "ぎ",
-                'す' => "し",
-                'つ' => "ち",
-                'ぬ' => "に",
-                'ふ' => "ひ",
-                'ぶ' => "び",
-                'む' => "み",
-                'る' => "り",
-                _ => return Err(VerbError::UnknownConjugation),
-            };
+            let new_ending = godan_ending(last_char, Column::I)?;
             Ok(format!("{}{}", stem, new_ending))
         }
         ConjugationType::KamiIchidan | ConjugationType::ShimoIchidan => {
@@ -69,8 +62,7 @@ pub fn continuative_form(verb: &str, conjugation: ConjugationType) -> Result<Str
         ConjugationType::Sahen => {
             if verb == "する" {
                 Ok("し".to_string())
-            } else if verb.ends_with("する") {
-                let stem = &verb[..verb.len() - "する".len()];
+            } else if let Some(stem) = verb.strip_suffix("する") {
                 Ok(format!("{}し", stem))
             } else {
                 Err(VerbError::UnknownConjugation)
@@ -154,6 +146,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_continuative_decomposed() {
+        assert_eq!(
+            continuative_form("ぬく\u{3099}", ConjugationType::Godan),
+            Ok("ぬぎ".to_string())
+        );
+        assert_eq!(
+            continuative_form("しらへ\u{3099}る", ConjugationType::ShimoIchidan),
+            Ok("しらべ".to_string())
+        );
+    }
+
+    #[test]
+    fn test_continuative_errors() {
+        assert_eq!(
+            continuative_form("", ConjugationType::Godan),
+            Err(VerbError::NotAVerb)
+        );
+        // Only the Hiragana endings of Godan verbs
+        assert_eq!(
+            continuative_form("カク", ConjugationType::Godan),
+            Err(VerbError::UnknownConjugation)
+        );
+        assert_eq!(
+            continuative_form("ぷぷ", ConjugationType::Godan),
+            Err(VerbError::UnknownConjugation)
+        );
+    }
+
     #[test]
     fn test_continuative_macro() {
         assert_eq!(continuative_form!("書く"), Ok("書き".to_string()));
@@ -1,3 +1,7 @@
+use crate::core::classify::{is_hiragana, is_kana_small};
+use crate::core::gojuon::{self, Column};
+use crate::core::kana::compose_sound_marks;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum ConjugationType {
     Godan,        // 五段
@@ -70,6 +74,9 @@ pub fn infer_conjugation_type(verb: &str) -> Result<ConjugationType, VerbError>
         return Err(VerbError::NotAVerb);
     }
 
+    // Decomposed input ("へ" + U+3099) is read as its composed form
+    let verb = compose_sound_marks(verb);
+    let verb = verb.as_str();
     let chars: Vec<char> = verb.chars().collect();
     let len = chars.len();
     let last_char = chars[len - 1];
@@ -109,44 +116,30 @@ pub fn infer_conjugation_type(verb: &str) -> Result<ConjugationType, VerbError>
 }
 
 fn is_i_sound(c: char) -> bool {
-    // Hiragana 'i' column
-    matches!(
-        c,
-        'い' | 'き'
-            | 'ぎ'
-            | 'し'
-            | 'じ'
-            | 'ち'
-            | 'ぢ'
-            | 'に'
-            | 'ひ'
-            | 'び'
-            | 'ぴ'
-            | 'み'
-            | 'り'
-            | '見'
-    )
+    // Full-size Hiragana in the 'i' column, or a Kanji read with one
+    c == '見' || is_full_size_hiragana_in(c, Column::I)
 }
 
 fn is_e_sound(c: char) -> bool {
-    // Hiragana 'e' column
-    matches!(
-        c,
-        'え' | 'け'
-            | 'げ'
-            | 'せ'
-            | 'ぜ'
-            | 'て'
-            | 'で'
-            | 'ね'
-            | 'へ'
-            | 'べ'
-            | 'ぺ'
-            | 'め'
-            | 'れ'
-            | '出'
-            | '寝'
-    )
+    // Full-size Hiragana in the 'e' column, or a Kanji read with one
+    matches!(c, '出' | '寝') || is_full_size_hiragana_in(c, Column::E)
+}
+
+fn is_full_size_hiragana_in(c: char, column: Column) -> bool {
+    is_hiragana(c)
+        && !is_kana_small(c)
+        && gojuon::position(c).is_some_and(|(_, found)| found == column)
+}
+
+/// The Hiragana a Godan verb can end in.
+const GODAN_ENDINGS: [char; 10] = ['う', 'く', 'ぐ', 'す', 'つ', 'ぬ', 'ふ', 'ぶ', 'む', 'る'];
+
+/// Move the final u-sound of a Godan verb to `column` (く -> か, き, ...).
+pub(crate) fn godan_ending(last_char: char, column: Column) -> Result<char, VerbError> {
+    if !GODAN_ENDINGS.contains(&last_char) {
+        return Err(VerbError::UnknownConjugation);
+    }
+    gojuon::shift_column(last_char, column).ok_or(VerbError::UnknownConjugation)
 }
 
 #[cfg(test)]
@@ -269,4 +262,42 @@ mod tests {
         assert_eq!(infer_conjugation_type("あ"), Err(VerbError::NotAVerb));
         assert_eq!(infer_conjugation_type("リンゴ"), Err(VerbError::NotAVerb));
     }
+
+    #[test]
+    fn test_small_and_katakana_before_ru() {
+        // Only full-size Hiragana (and 見, 出, 寝) make an Ichidan verb
+        assert_eq!(infer_conjugation_type("ぃる"), Ok(ConjugationType::Godan));
+        assert_eq!(infer_conjugation_type("ぇる"), Ok(ConjugationType::Godan));
+        assert_eq!(infer_conjugation_type("キる"), Ok(ConjugationType::Godan));
+        assert_eq!(
+            infer_conjugation_type("みる"),
+            Ok(ConjugationType::KamiIchidan)
+        );
+    }
+
+    #[test]
+    fn test_decomposed_sound_marks() {
+        assert_eq!(
+            infer_conjugation_type("しらへ\u{3099}る"),
+            Ok(ConjugationType::ShimoIchidan)
+        );
+        assert_eq!(
+            infer_conjugation_type("か\u{3099}んは\u{3099}る"),
+            Ok(ConjugationType::Godan)
+        );
+    }
+
+    #[test]
+    fn test_godan_ending() {
+        assert_eq!(godan_ending('く', Column::A), Ok('か'));
+        assert_eq!(godan_ending('う', Column::I), Ok('い'));
+        for c in ['ぷ', 'ず', 'ゆ', 'っ', 'ク', 'か'] {
+            assert_eq!(
+                godan_ending(c, Column::A),
+                Err(VerbError::UnknownConjugation),
+                "{}",
+                c
+            );
+        }
+    }
 }
@@ -1,4 +1,6 @@
-use super::infer_conjugation_type::{ConjugationType, VerbError};
+use super::infer_conjugation_type::{ConjugationType, VerbError, godan_ending};
+use crate::core::gojuon::Column;
+use crate::core::kana::compose_sound_marks;
 
 /// Conjugate a Japanese verb to its Irrealis form (Mizenkei).
 ///
@@ -32,6 +34,9 @@ pub fn irrealis_form(verb: &str, conjugation: ConjugationType) -> Result<String,
         return Err(VerbError::NotAVerb);
     }
 
+    // Decomposed input ("く" + U+3099) conjugates like its composed form
+    let verb = compose_sound_marks(verb);
+    let verb = verb.as_str();
     let chars: Vec<char> = verb.chars().collect();
     let len = chars.len();
 
@@ -47,17 +52,8 @@ pub fn irrealis_form(verb: &str, conjugation: ConjugationType) -> Result<String,
             let stem = &verb[..verb.len() - last_char.len_utf8()];
 
             let new_ending = match last_char {
-                'う' => "わ",
-                'く' => "か",
-                'ぐ' => "が",
-                'す' => "さ",
-                'つ' => "た",
-                'ぬ' => "な",
-                'ふ' => "は",
-                'ぶ' => "ば",
-                'む' => "ま",
-                'る' => "ら",
-                _ => return Err(VerbError::UnknownConjugation),
+                'う' => 'わ',
+                _ => godan_ending(last_char, Column::A)?,
             };
             Ok(format!("{}{}", stem, new_ending))
         }
@@ -70,8 +66,7 @@ pub fn irrealis_form(verb: &str, conjugation: ConjugationType) -> Result<String,
         ConjugationType::Sahen => {
             if verb == "する" {
                 Ok("し".to_string())
-            } else if verb.ends_with("する") {
-                let stem = &verb[..verb.len() - "する".len()];
+            } else if let Some(stem) = verb.strip_suffix("する") {
                 Ok(format!("{}し", stem))
             } else {
                 Err(VerbError::UnknownConjugation)
@@ -169,6 +164,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_irrealis_decomposed() {
+        assert_eq!(
+            irrealis_form("ぬく\u{3099}", ConjugationType::Godan),
+            Ok("ぬが".to_string())
+        );
+        assert_eq!(
+            irrealis_form("しらへ\u{3099}る", ConjugationType::ShimoIchidan),
+            Ok("しらべ".to_string())
+        );
+    }
+
     #[test]
     fn test_irrealis_errors() {
         assert_eq!(
@@ -180,5 +187,14 @@ mod tests {
             irrealis_form("書く", ConjugationType::KamiIchidan),
             Err(VerbError::UnknownConjugation)
         );
+        // Only the Hiragana endings of Godan verbs
+        assert_eq!(
+            irrealis_form("ぷぷ", ConjugationType::Godan),
+            Err(VerbError::UnknownConjugation)
+        );
+        assert_eq!(
+            irrealis_form("カク", ConjugationType::Godan),
+            Err(VerbError::UnknownConjugation)
+        );
     }
 }